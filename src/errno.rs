@@ -0,0 +1,151 @@
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+
+use libc::{
+    c_int, EACCES, EAGAIN, EBADF, EBUSY, EFAULT, EINTR, EINVAL, EIO, ENFILE, ENODEV, ENOMEM,
+    ENOSYS, EOVERFLOW, EPERM, ETXTBSY,
+};
+
+/// A raw `errno` value, shared by `LockError` and `AdviseError`
+/// for their formatting and by the `mmap` errors of `MappedBuffer`.
+/// # Examples
+///
+/// ```
+/// use memguar::Errno;
+///
+/// let errno = Errno::from(libc::ENOMEM);
+///
+/// assert_eq!(errno.raw(), libc::ENOMEM);
+/// assert_eq!(errno.name(), Some("ENOMEM"));
+/// assert_eq!(errno.to_string(), "cannot allocate memory");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Errno(c_int);
+
+impl Errno {
+    /// Returns the current `errno` of the calling thread
+    pub fn last() -> Self {
+        Errno(last_errno())
+    }
+
+    /// Returns the raw `errno` value
+    pub fn raw(self) -> c_int {
+        self.0
+    }
+
+    /// Returns the name of the `errno` constant, or `None` if it is not known to the crate
+    pub fn name(self) -> Option<&'static str> {
+        self.describe().map(|(name, _)| name)
+    }
+
+    fn describe(self) -> Option<(&'static str, &'static str)> {
+        let description = match self.0 {
+            EPERM => ("EPERM", "operation not permitted"),
+            EINTR => ("EINTR", "interrupted system call"),
+            EIO => ("EIO", "input/output error"),
+            EBADF => ("EBADF", "bad file descriptor"),
+            EAGAIN => ("EAGAIN", "resource temporarily unavailable"),
+            ENOMEM => ("ENOMEM", "cannot allocate memory"),
+            EACCES => ("EACCES", "permission denied"),
+            EFAULT => ("EFAULT", "bad address"),
+            EBUSY => ("EBUSY", "device or resource busy"),
+            ENODEV => ("ENODEV", "no such device"),
+            EINVAL => ("EINVAL", "invalid argument"),
+            ENFILE => ("ENFILE", "too many open files in system"),
+            ETXTBSY => ("ETXTBSY", "text file busy"),
+            ENOSYS => ("ENOSYS", "function not implemented"),
+            EOVERFLOW => ("EOVERFLOW", "value too large for defined data type"),
+            _ => return None,
+        };
+
+        Some(description)
+    }
+}
+
+impl From<c_int> for Errno {
+    fn from(err: c_int) -> Self {
+        Errno(err)
+    }
+}
+
+impl Display for Errno {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.describe() {
+            Some((_, description)) => write!(f, "{description}"),
+            None => write!(f, "unknown error (errno {})", self.0),
+        }
+    }
+}
+
+impl Error for Errno {}
+
+/// Returns the current `errno` of the calling thread
+#[cfg(feature = "std")]
+pub(crate) fn last_errno() -> c_int {
+    std::io::Error::last_os_error()
+        .raw_os_error()
+        .unwrap_or(-1)
+}
+
+/// Returns the current `errno` of the calling thread
+#[cfg(not(feature = "std"))]
+pub(crate) fn last_errno() -> c_int {
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "emscripten",
+        target_os = "hurd",
+        target_os = "redox",
+    ))]
+    // SAFETY: FFI. Always returns a valid pointer to the thread's `errno`
+    let errno = unsafe { *libc::__errno_location() };
+
+    #[cfg(any(target_vendor = "apple", target_os = "freebsd"))]
+    // SAFETY: FFI. Always returns a valid pointer to the thread's `errno`
+    let errno = unsafe { *libc::__error() };
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "cygwin",
+        target_os = "netbsd",
+        target_os = "openbsd",
+    ))]
+    // SAFETY: FFI. Always returns a valid pointer to the thread's `errno`
+    let errno = unsafe { *libc::__errno() };
+
+    #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+    // SAFETY: FFI. Always returns a valid pointer to the thread's `errno`
+    let errno = unsafe { *libc::___errno() };
+
+    #[cfg(target_os = "haiku")]
+    // SAFETY: FFI. Always returns a valid pointer to the thread's `errno`
+    let errno = unsafe { *libc::_errnop() };
+
+    #[cfg(target_os = "aix")]
+    // SAFETY: FFI. Always returns a valid pointer to the thread's `errno`
+    let errno = unsafe { *libc::_Errno() };
+
+    errno
+}
+
+#[cfg(all(
+    not(feature = "std"),
+    not(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "emscripten",
+        target_os = "hurd",
+        target_os = "redox",
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "android",
+        target_os = "cygwin",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku",
+        target_os = "aix",
+    )),
+))]
+compile_error!("Reading `errno` without the `std` feature isn't supported on this target");
@@ -0,0 +1,328 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind};
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+use std::ptr;
+
+use libc::{c_int, MAP_ANONYMOUS, MAP_PRIVATE, MAP_SHARED, mprotect, PROT_READ, PROT_WRITE};
+use tempfile::{tempfile, tempfile_in};
+
+use crate::file::mapper::{byte_size, map, page_faults, MappedBuffer};
+use crate::page::page_size;
+
+/// A builder that combines the mapping options of `MappedBuffer`
+/// into the `prot` and `flags` bitmasks passed to `mmap`.
+/// # Examples
+///
+/// ```
+/// use memguar::builder::MappedBufferBuilder;
+///
+/// pub fn builder_example() -> Result<(), std::io::Error> {
+///     let buf = [420; 16_000];
+///     let _mapped_buf = MappedBufferBuilder::new()
+///         .shared(false)
+///         .populate(true)
+///         .build_from(buf)?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MappedBufferBuilder {
+    shared: bool,
+    writable: bool,
+    populate: bool,
+    huge_pages: bool,
+    memfd: bool,
+    round_to_pages: bool,
+    track_faults: bool,
+    path: Option<PathBuf>,
+    temp_dir: Option<PathBuf>,
+}
+
+impl MappedBufferBuilder {
+    pub fn new() -> Self {
+        Self {
+            shared: true,
+            writable: true,
+            populate: false,
+            huge_pages: false,
+            memfd: false,
+            round_to_pages: false,
+            track_faults: false,
+            path: None,
+            temp_dir: None,
+        }
+    }
+
+    /// `MAP_SHARED` if `true` (default), copy-on-write `MAP_PRIVATE` otherwise.
+    pub fn shared(mut self, shared: bool) -> Self {
+        self.shared = shared;
+        self
+    }
+
+    /// `PROT_READ | PROT_WRITE` if `true` (default), `PROT_READ` otherwise.
+    /// Mutable access to a read-only buffer panics.
+    pub fn writable(mut self, writable: bool) -> Self {
+        self.writable = writable;
+        self
+    }
+
+    /// Prefault all pages before building, `MAP_POPULATE` on Linux.
+    pub fn populate(mut self, populate: bool) -> Self {
+        self.populate = populate;
+        self
+    }
+
+    /// Back the buffer with huge pages (`MAP_HUGETLB`), Linux only.
+    /// Without a `path`, `build_from` maps anonymous memory instead of a tempfile.
+    pub fn huge_pages(mut self, huge_pages: bool) -> Self {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    /// Back the buffer with an in-memory file (`memfd_create`) instead of a tempfile,
+    /// so nothing touches the filesystem, Linux only. Ignored if a `path` is set.
+    /// `MappedBuffer::set_zeroize` overwrites the memfd, which may be shared with others.
+    pub fn memfd(mut self, memfd: bool) -> Self {
+        self.memfd = memfd;
+        self
+    }
+
+    /// Round the mapping (and the backing file) up to a page boundary,
+    /// so `MappedBuffer::resize` can grow into the rest of the last page without remapping.
+    /// The rounded size is reported by `MappedBuffer::capacity_bytes`.
+    pub fn round_to_pages(mut self, round_to_pages: bool) -> Self {
+        self.round_to_pages = round_to_pages;
+        self
+    }
+
+    /// Record the page faults of the process when building, so `MappedBuffer::stats`
+    /// reports the faults since then, Linux only. Reads `/proc/self/stat` once per build.
+    pub fn track_faults(mut self, track_faults: bool) -> Self {
+        self.track_faults = track_faults;
+        self
+    }
+
+    /// Map the file at `path` instead of a tempfile (or an anonymous mapping).
+    /// The file is created if needed, extended if it is shorter than the buffer
+    /// (but never truncated) and is not unlinked on drop.
+    /// If the mapping is `shared`, `MappedBuffer::set_zeroize` overwrites the file on drop.
+    pub fn path(mut self, path: Option<PathBuf>) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Create the backing tempfile in `temp_dir` instead of the system temp directory,
+    /// which may be a RAM-backed tmpfs. Ignored if a `path` is set or `memfd` is used.
+    pub fn temp_dir(mut self, temp_dir: Option<PathBuf>) -> Self {
+        self.temp_dir = temp_dir;
+        self
+    }
+
+    /// Creates a mapping holding a copy of the buffer.
+    pub fn build_from<T: Copy, B: AsRef<[T]>>(&self, buf: B) -> Result<MappedBuffer<T>, Error> {
+        let buf = buf.as_ref();
+        let len = buf.len();
+        let size = self.capacity(byte_size::<T>(len)?)?;
+        let flags = self.flags()?;
+        // The mapping is owned by `mapped_buf` right after `mmap` succeeds,
+        // so it is unmapped on every early return below, and on panic
+        let mut mapped_buf = match (&self.path, self.huge_pages) {
+            // `MAP_HUGETLB` only works for anonymous mappings or files on hugetlbfs
+            (None, true) => {
+                let ptr = map(size, PROT_READ | PROT_WRITE, flags | MAP_ANONYMOUS, -1, 0)?;
+                MappedBuffer::from_raw(ptr, size, true, None)
+            }
+            _ => {
+                let file = self.open()?;
+
+                // A longer file at `path` keeps the data past the buffer
+                if file.metadata()?.len() < size as u64 {
+                    file.set_len(size as u64)?;
+                }
+
+                match map(size, PROT_READ | PROT_WRITE, flags, file.as_raw_fd(), 0) {
+                    Ok(ptr) => MappedBuffer::from_raw(ptr, size, true, Some(file)),
+                    Err(err) => {
+                        // Close (and unlink) the backing tempfile before reporting the error
+                        drop(file);
+                        return Err(err);
+                    }
+                }
+            }
+        };
+
+        mapped_buf.len = len;
+        self.track(&mut mapped_buf);
+        // `byte_size` rejects element types aligned beyond the page size
+        debug_assert!(mapped_buf.ptr.cast::<T>().is_aligned());
+
+        // SAFETY: The pointer `mapped_buf.ptr` is valid for writing `buf.len()` elements
+        // and page aligned, so properly aligned for type `T`.
+        unsafe {
+            ptr::copy_nonoverlapping(buf.as_ptr(), mapped_buf.ptr.cast(), buf.len());
+        }
+
+        if !self.writable {
+            // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `mprotect` result
+            let result = unsafe {
+                mprotect(mapped_buf.ptr, mapped_buf.size, PROT_READ)
+            };
+
+            match result {
+                0 => mapped_buf.writable = false,
+                _ => return Err(Error::last_os_error()),
+            }
+        }
+
+        self.prefault(&mapped_buf);
+
+        Ok(mapped_buf)
+    }
+
+    /// Creates a zero-initialized mapping of `len` elements, that is anonymous (`MAP_ANONYMOUS`)
+    /// unless a `path` is set, in which case the file is extended to hold `len` elements.
+    ///
+    /// # Safety
+    ///
+    /// All zero bytes must be a valid `T`, which holds for integers, floats and raw pointers,
+    /// but not for references, `NonZero*`, `char` or most enums.
+    /// If a `path` is set, the existing contents of the file are reinterpreted as `T` as well,
+    /// so they must be valid `T`s too.
+    pub unsafe fn build_anon<T: Copy>(&self, len: usize) -> Result<MappedBuffer<T>, Error> {
+        let size = self.capacity(byte_size::<T>(len)?)?;
+        let prot = match self.writable {
+            true => PROT_READ | PROT_WRITE,
+            false => PROT_READ,
+        };
+        let flags = self.flags()?;
+        let (ptr, file) = match &self.path {
+            Some(_) => {
+                let file = self.open()?;
+
+                if file.metadata()?.len() < size as u64 {
+                    file.set_len(size as u64)?;
+                }
+
+                (map(size, prot, flags, file.as_raw_fd(), 0)?, Some(file))
+            }
+            None => (map(size, prot, flags | MAP_ANONYMOUS, -1, 0)?, None),
+        };
+        let mut mapped_buf = MappedBuffer::from_raw(ptr, size, self.writable, file);
+        mapped_buf.len = len;
+        self.track(&mut mapped_buf);
+
+        self.prefault(&mapped_buf);
+
+        Ok(mapped_buf)
+    }
+
+    /// Size of the mapping holding `size` bytes
+    fn capacity(&self, size: usize) -> Result<usize, Error> {
+        match self.round_to_pages {
+            true => size.checked_next_multiple_of(page_size()).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, "Buffer size overflows usize")
+            }),
+            false => Ok(size),
+        }
+    }
+
+    fn track<T: Copy>(&self, mapped_buf: &mut MappedBuffer<T>) {
+        if self.track_faults {
+            mapped_buf.faults = page_faults();
+        }
+    }
+
+    fn flags(&self) -> Result<c_int, Error> {
+        let mut flags = match self.shared {
+            true => MAP_SHARED,
+            false => MAP_PRIVATE,
+        };
+
+        #[cfg(target_os = "linux")]
+        {
+            if self.populate {
+                flags |= libc::MAP_POPULATE;
+            }
+            if self.huge_pages {
+                flags |= libc::MAP_HUGETLB;
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        if self.huge_pages {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Huge pages are only supported on Linux",
+            ));
+        }
+
+        Ok(flags)
+    }
+
+    fn open(&self) -> Result<File, Error> {
+        match &self.path {
+            Some(path) => OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path),
+            None if self.memfd => memfd(),
+            None => match &self.temp_dir {
+                Some(temp_dir) => tempfile_in(temp_dir),
+                None => tempfile(),
+            },
+        }
+    }
+
+    /// Touches each page of the buffer, since `MAP_POPULATE` is Linux only.
+    #[cfg(not(target_os = "linux"))]
+    fn prefault<T: Copy>(&self, mapped_buf: &MappedBuffer<T>) {
+        if !self.populate {
+            return;
+        }
+
+        for offset in (0..mapped_buf.size).step_by(page_size()) {
+            // SAFETY: `offset` is within the mapping, which is valid for reading
+            unsafe {
+                ptr::read_volatile(mapped_buf.ptr.cast::<u8>().add(offset));
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn prefault<T: Copy>(&self, _mapped_buf: &MappedBuffer<T>) {}
+}
+
+/// Creates an anonymous in-memory file
+#[cfg(target_os = "linux")]
+fn memfd() -> Result<File, Error> {
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: FFI. Valid NUL-terminated name and flags + processed `memfd_create` result
+    let fd = unsafe {
+        libc::memfd_create(c"memguar".as_ptr(), libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING)
+    };
+
+    match fd {
+        -1 => Err(Error::last_os_error()),
+        // SAFETY: `fd` is a newly created descriptor that nothing else owns
+        fd => Ok(unsafe { File::from_raw_fd(fd) }),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn memfd() -> Result<File, Error> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "memfd is only supported on Linux",
+    ))
+}
+
+impl Default for MappedBufferBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,16 +1,34 @@
-use std::{panic, ptr};
-use std::io::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::ptr;
+use std::io::{Error, ErrorKind};
 use std::marker::PhantomData;
-use std::ops::Deref;
-use std::os::fd::AsRawFd;
+use std::ops::{BitOr, Deref, DerefMut, Index, IndexMut, RangeBounds};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::path::Path;
+use std::slice::{Chunks, Iter, SliceIndex};
 
-use libc::{c_void, MAP_SHARED, mmap, munmap, PROT_READ, PROT_WRITE, size_t};
+use libc::{
+    c_int, c_void, MADV_DONTNEED, madvise, MAP_SHARED, mincore, mlock, mmap, mprotect, MS_ASYNC,
+    MS_INVALIDATE, MS_SYNC, msync, munlock, munmap, off_t, posix_madvise, PROT_READ, PROT_WRITE,
+    size_t,
+};
 use tempfile::tempfile;
 
+use crate::errno::Errno;
+use crate::file::builder::MappedBufferBuilder;
+use crate::page::page_size;
+use crate::wrappers::advisor::{Advise, AdviseError};
+use crate::wrappers::locker::LockError;
+
 /// A struct that represents a buffer that is mapped to memory.
 ///
 /// The `MappedBuffer` struct provides a safe and convenient way to create a buffer that is mapped to memory,
 /// allowing you to read and write data to the buffer using a slice-like interface.
+///
+/// Mappings are page aligned, so the alignment of `T` must not exceed the page size,
+/// otherwise the constructors return `ErrorKind::InvalidInput`.
 /// # Examples
 ///
 /// ```
@@ -24,87 +42,2069 @@ use tempfile::tempfile;
 /// }
 /// ```
 pub struct MappedBuffer<T: Copy> {
-    size: usize,
-    ptr: *mut c_void,
+    pub(crate) size: usize,
+    /// Number of elements, that may take up less than the mapping
+    pub(crate) len: usize,
+    pub(crate) ptr: *mut c_void,
+    pub(crate) writable: bool,
+    zeroize: bool,
+    file: Option<File>,
+    /// Bytes between the page aligned `ptr` and the first element
+    offset: usize,
+    /// Offset of `ptr` within the backing file
+    file_offset: u64,
+    /// Minor and major page faults of the process when the buffer was mapped,
+    /// only read if the builder's `track_faults` is set
+    pub(crate) faults: Option<(u64, u64)>,
     _phantom: PhantomData<T>,
 }
 
 impl<T: Copy> MappedBuffer<T> {
+    /// Takes ownership of a mapping of `size` bytes at `ptr`, which is unmapped on drop,
+    /// and of its backing `file`, if any.
+    pub(crate) fn from_raw(
+        ptr: *mut c_void,
+        size: usize,
+        writable: bool,
+        file: Option<File>,
+    ) -> Self {
+        Self {
+            size,
+            len: size / size_of::<T>(),
+            ptr,
+            writable,
+            zeroize: false,
+            file,
+            offset: 0,
+            file_offset: 0,
+            faults: None,
+            _phantom: PhantomData,
+        }
+    }
+
     pub fn new<B: AsRef<[T]>>(buf: B) -> Result<Self, Error> {
+        MappedBufferBuilder::new().build_from(buf)
+    }
+
+    /// Creates a mapping of the buffer and locks its pages according to `policy`,
+    /// so the memory/latency tradeoff is picked in one call.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::{LockPolicy, MappedBuffer};
+    ///
+    /// pub fn new_with_example() -> Result<(), memguar::mapper::MapError> {
+    ///     let buf = [420; 16_000];
+    ///     let _mapped_buf = MappedBuffer::new_with(buf, LockPolicy::Eager)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_with<B: AsRef<[T]>>(buf: B, policy: LockPolicy) -> Result<Self, MapError> {
+        let mapped_buf = Self::new(buf)?;
+
+        match policy {
+            LockPolicy::None => {}
+            LockPolicy::Eager => mapped_buf.lock()?,
+            LockPolicy::OnFault => mapped_buf.lock_on_fault()?,
+        }
+
+        Ok(mapped_buf)
+    }
+
+    /// Creates a copy-on-write (`MAP_PRIVATE`) mapping of the buffer.
+    ///
+    /// Unlike `new`, writes to the mapping are visible to this process only,
+    /// are never carried through to the backing file, and are discarded on drop.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn new_private_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mut mapped_buf = MappedBuffer::new_private(buf)?;
+    ///     mapped_buf[0] = 69;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_private<B: AsRef<[T]>>(buf: B) -> Result<Self, Error> {
+        MappedBufferBuilder::new()
+            .shared(false)
+            .build_from(buf)
+    }
+
+    /// Creates a read-only mapping of the buffer (`PROT_READ`).
+    ///
+    /// The returned `ReadOnlyMappedBuffer` only gives shared access to the elements,
+    /// and any write to the mapped pages faults.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn new_readonly_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new_readonly(buf)?;
+    ///     let _buf = mapped_buf.receive();
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_readonly<B: AsRef<[T]>>(buf: B) -> Result<ReadOnlyMappedBuffer<T>, Error> {
+        let mapped_buf = MappedBufferBuilder::new()
+            .writable(false)
+            .build_from(buf)?;
+
+        Ok(ReadOnlyMappedBuffer(mapped_buf))
+    }
+
+    /// Creates a mapping of the buffer whose pages are all prefaulted before returning,
+    /// so the first access doesn't stall on a page fault.
+    /// On Linux this is done by `MAP_POPULATE`, elsewhere by touching each page.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn new_populated_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let _mapped_buf = MappedBuffer::new_populated(buf)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_populated<B: AsRef<[T]>>(buf: B) -> Result<Self, Error> {
+        MappedBufferBuilder::new()
+            .populate(true)
+            .build_from(buf)
+    }
+
+    /// Creates an anonymous mapping of the buffer backed by huge pages (`MAP_HUGETLB`),
+    /// which reduces TLB pressure for multi-gigabyte buffers.
+    ///
+    /// The size of the buffer must be a multiple of the default huge page size
+    /// (`Hugepagesize` in `/proc/meminfo`), `EINVAL` is returned otherwise.
+    /// Huge pages must be reserved beforehand (`vm.nr_hugepages`).
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn new_huge_example() -> Result<(), std::io::Error> {
+    ///     let buf = vec![0u8; 2 * 1024 * 1024];
+    ///     let _mapped_buf = MappedBuffer::new_huge(buf)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn new_huge<B: AsRef<[T]>>(buf: B) -> Result<Self, Error> {
         let buf = buf.as_ref();
-        assert!(size_of_val(buf) > 0, "Zero size buffer");
-        let size = size_of_val(buf);
+
+        if size_of_val(buf) % huge_page_size()? != 0 {
+            return Err(Error::from_raw_os_error(libc::EINVAL));
+        }
+
+        MappedBufferBuilder::new()
+            .huge_pages(true)
+            .build_from(buf)
+            .map_err(|err| match err.kind() {
+                ErrorKind::OutOfMemory => {
+                    Error::new(err.kind(), format!("Huge pages are unavailable: {err}"))
+                }
+                _ => err,
+            })
+    }
+
+    /// Creates a mapping of the buffer backed by an in-memory file (`memfd_create`)
+    /// instead of a tempfile, so it keeps the shared mapping semantics without
+    /// needing a writable temp directory.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn new_memfd_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let _mapped_buf = MappedBuffer::new_memfd(buf)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn new_memfd<B: AsRef<[T]>>(buf: B) -> Result<Self, Error> {
+        MappedBufferBuilder::new()
+            .memfd(true)
+            .build_from(buf)
+    }
+
+    /// Creates a mapping of the buffer backed by a tempfile in `dir`,
+    /// instead of the system temp directory used by `new`.
+    ///
+    /// Point it at a directory on disk if the temp directory is a tmpfs,
+    /// since a tmpfs keeps the file in RAM (or swap), which is what mapping is meant to save.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn new_in_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let _mapped_buf = MappedBuffer::new_in(buf, std::env::current_dir()?)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_in<B: AsRef<[T]>, P: AsRef<Path>>(buf: B, dir: P) -> Result<Self, Error> {
+        MappedBufferBuilder::new()
+            .temp_dir(Some(dir.as_ref().to_path_buf()))
+            .build_from(buf)
+    }
+
+    /// If `seal` is successful, the backing memfd of the buffer is sealed (`F_ADD_SEALS`),
+    /// so nobody holding its descriptor can resize or write it anymore,
+    /// which makes it safe to share as a read-only snapshot with another process.
+    ///
+    /// Only buffers created by `new_memfd` can be sealed, others return an error.
+    /// `Seals::WRITE` fails with `EBUSY` while the buffer itself is mapped writable,
+    /// `Seals::FUTURE_WRITE` only forbids new writable mappings and writes through the descriptor.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::{MappedBuffer, Seals};
+    ///
+    /// pub fn seal_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new_memfd(buf)?;
+    ///     mapped_buf.seal(Seals::SHRINK | Seals::GROW | Seals::FUTURE_WRITE)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn seal(&self, seals: Seals) -> Result<(), Error> {
+        let Some(file) = &self.file else {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Anonymous mappings can't be sealed",
+            ));
+        };
+
+        // SAFETY: FFI. Valid raw file descriptor and seals + processed `fcntl` result
+        let result = unsafe {
+            libc::fcntl(file.as_raw_fd(), libc::F_ADD_SEALS, seals.0)
+        };
+
+        match result {
+            -1 => Err(Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates a zero-initialized buffer of `len` elements
+    /// that is mapped without any backing file (`MAP_ANONYMOUS`).
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn anonymous_example() -> Result<(), std::io::Error> {
+    ///     // SAFETY: All zero bytes are a valid `u64`
+    ///     let mapped_buf = unsafe { MappedBuffer::<u64>::anonymous(16_000)? };
+    ///     assert_eq!(mapped_buf[0], 0);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// All zero bytes must be a valid `T`, which holds for integers, floats and raw pointers,
+    /// but not for references, `NonZero*`, `char` or most enums.
+    /// With the `bytemuck` feature, `anonymous_pod` checks this at compile time.
+    pub unsafe fn anonymous(len: usize) -> Result<Self, Error> {
+        // SAFETY: Same contract as `anonymous`, no `path` is set
+        unsafe { MappedBufferBuilder::new().build_anon(len) }
+    }
+
+    /// Creates a zero-initialized buffer of `len` elements, mapped to a tempfile like `new`,
+    /// so space can be reserved without copying existing data into it.
+    ///
+    /// Unlike the other constructors, `len` may be `0`: the buffer is then empty,
+    /// but still maps a page (`mmap` rejects zero sizes), which `resize` can grow into.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn with_capacity_example() -> Result<(), std::io::Error> {
+    ///     // SAFETY: All zero bytes are a valid `u64`
+    ///     let mut mapped_buf = unsafe { MappedBuffer::<u64>::with_capacity(16_000)? };
+    ///     assert_eq!(mapped_buf[0], 0);
+    ///     mapped_buf[0] = 420;
+    ///
+    ///     // SAFETY: An empty buffer holds no zeroed elements
+    ///     let empty_buf = unsafe { MappedBuffer::<u64>::with_capacity(0)? };
+    ///     assert!(empty_buf.is_empty());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// Same as `anonymous`, all zero bytes must be a valid `T`, unless `len` is `0`.
+    pub unsafe fn with_capacity(len: usize) -> Result<Self, Error> {
+        let size = match len {
+            0 => byte_size::<T>(1)?.next_multiple_of(page_size()),
+            len => byte_size::<T>(len)?,
+        };
         let file = tempfile()?;
 
         file.set_len(size as u64)?;
 
-        // SAFETY: FFI. Safe cast (`size as size_t` = usize as usize)
-        // Valid raw file descriptor for temp-phys file + processed `mmap` result
-        let ptr = unsafe {
-            mmap(
-                ptr::null_mut(),
-                size as size_t,
-                PROT_READ | PROT_WRITE,
-                MAP_SHARED,
-                file.as_raw_fd(),
-                0,
-            )
-        };
-
-        match ptr {
-            libc::MAP_FAILED => panic!("{}", Error::last_os_error()),
-            _ =>
-                if ptr.cast::<T>().is_aligned() {
-                    // SAFETY: The pointer `ptr` is valid for writing
-                    // `buf.len()` bytes and that these bytes are properly aligned for type `T`.
-                    unsafe {
-                        ptr::copy_nonoverlapping(buf.as_ptr(), ptr.cast(), buf.len());
-                    }
-                },
-        };
+        let ptr = map(size, PROT_READ | PROT_WRITE, MAP_SHARED, file.as_raw_fd(), 0)?;
+        let mut mapped_buf = Self::from_raw(ptr, size, true, Some(file));
+        mapped_buf.len = len;
 
-        Ok(Self {
-            ptr,
-            size,
-            _phantom: PhantomData,
-        })
+        Ok(mapped_buf)
     }
-    /// If `receive` is successful, It returns a slice that represents the mapped buffer.
+
+    /// Creates a buffer of `len` elements that is mapped to the file at `path`,
+    /// creating the file if it doesn't exist and resizing it to exactly `len` elements.
+    ///
+    /// Unlike `new`, the file is not unlinked on drop, so its contents outlive the process.
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn from_path_example() -> Result<(), std::io::Error> {
+    ///     // SAFETY: Any bytes are a valid `u64`
+    ///     let mut mapped_buf = unsafe { MappedBuffer::<u64>::from_path("buf.bin", 16_000)? };
+    ///     mapped_buf[0] = 420;
+    ///     mapped_buf.flush()?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The contents of the file are reinterpreted as `T`, so every element it holds
+    /// must be a valid `T`, and all zero bytes too if the file is created or extended.
+    /// With the `bytemuck` feature, `from_path_pod` checks this at compile time.
+    pub unsafe fn from_path<P: AsRef<Path>>(path: P, len: usize) -> Result<Self, Error> {
+        let builder = MappedBufferBuilder::new().path(Some(path.as_ref().to_path_buf()));
+        // SAFETY: Same contract as `from_path`
+        unsafe { builder.build_anon(len) }
+    }
+
+    /// Creates a buffer of `len` elements that is mapped to an already open `file`,
+    /// such as a memfd or a descriptor shared by another process.
+    /// The file must be open for reading and writing, it is extended if it is too short
+    /// (but never truncated), and is kept open until the buffer is unmapped.
     /// # Examples
     ///
     /// ```
     /// use memguar::mapper::MappedBuffer;
     ///
-    /// pub fn receive_example() -> Result<(), std::io::Error> {
-    ///     let buf = [420; 16_000];
-    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    /// pub fn from_file_example() -> Result<(), std::io::Error> {
+    ///     let file = tempfile::tempfile()?;
+    ///     // SAFETY: Any bytes are a valid `u64`
+    ///     let mut mapped_buf = unsafe { MappedBuffer::<u64>::from_file(file, 16_000)? };
+    ///     mapped_buf[0] = 420;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The contents of the file are reinterpreted as `T`, so every element it holds
+    /// must be a valid `T`, and all zero bytes too if the file is extended.
+    /// With the `bytemuck` feature, `from_file_pod` checks this at compile time.
+    pub unsafe fn from_file(file: File, len: usize) -> Result<Self, Error> {
+        let size = byte_size::<T>(len)?;
+
+        if file.metadata()?.len() < size as u64 {
+            file.set_len(size as u64)?;
+        }
+
+        let ptr = map(size, PROT_READ | PROT_WRITE, MAP_SHARED, file.as_raw_fd(), 0)?;
+
+        Ok(Self::from_raw(ptr, size, true, Some(file)))
+    }
+
+    /// Creates a buffer of `len` elements that is mapped to the region of the file at `path`
+    /// starting at the byte `offset`, without mapping the rest of the file.
+    ///
+    /// The offset doesn't need to be page aligned, the mapping starts at the page containing it
+    /// and the buffer only exposes the requested region. The file must hold the whole region,
+    /// it is not extended, nor unlinked on drop. Growing the buffer with `resize`
+    /// exposes the bytes that follow the region in the file as they are.
+    /// Returns `UnexpectedEof` if the file is too short, and `InvalidInput`
+    /// if the offset doesn't fit into `off_t`.
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn map_region_example() -> Result<(), std::io::Error> {
+    ///     // SAFETY: Any bytes are a valid `u64`
+    ///     let mapped_buf = unsafe { MappedBuffer::<u64>::map_region("huge.bin", 1 << 32, 16)? };
     ///     let _buf = mapped_buf.receive();
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn receive(&self) -> &[T] {
-        // SAFETY: The pointer `self.ptr` is valid for
-        // reading `self.size` bytes and that these bytes are properly aligned for type `T`.
-        unsafe {
-            std::slice::from_raw_parts(self.ptr.cast(), self.size / size_of::<T>())
+    ///
+    /// # Safety
+    ///
+    /// The region of the file is reinterpreted as `T`, so every element it holds
+    /// must be a valid `T`.
+    /// With the `bytemuck` feature, `map_region_pod` checks this at compile time.
+    pub unsafe fn map_region<P: AsRef<Path>>(
+        path: P,
+        offset: u64,
+        len: usize,
+    ) -> Result<Self, Error> {
+        let region_size = byte_size::<T>(len)?;
+        let page_size = page_size() as u64;
+        let file_offset = offset - offset % page_size;
+        let delta = (offset - file_offset) as usize;
+
+        if !delta.is_multiple_of(align_of::<T>()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Offset is misaligned for the element type",
+            ));
+        }
+
+        let size = delta.checked_add(region_size);
+        let file_len = offset.checked_add(region_size as u64);
+        let (Some(size), Some(file_len)) = (size, file_len) else {
+            return Err(Error::new(ErrorKind::InvalidInput, "Buffer size overflows usize"));
+        };
+        let Ok(map_offset) = off_t::try_from(file_offset) else {
+            return Err(Error::new(ErrorKind::InvalidInput, "Offset overflows off_t"));
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        if file.metadata()?.len() < file_len {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "File is too short for the region",
+            ));
         }
+
+        let ptr = map(size, PROT_READ | PROT_WRITE, MAP_SHARED, file.as_raw_fd(), map_offset)?;
+        let mut mapped_buf = Self::from_raw(ptr, size, true, Some(file));
+        mapped_buf.len = len;
+        mapped_buf.offset = delta;
+        mapped_buf.file_offset = file_offset;
+
+        Ok(mapped_buf)
     }
-}
 
-impl<T: Copy> Deref for MappedBuffer<T> {
-    type Target = [T];
+    /// If `resize` is successful, the mapped buffer holds `new_len` elements.
+    /// The added elements that fit into the old `capacity_bytes` are set to zero,
+    /// including those left over from shrinking, even if the buffer is read-only.
+    /// The ones past it are read from the extended part of the backing file,
+    /// which is zero unless the file already extends beyond the mapping (see `map_region`).
+    ///
+    /// If the elements fit into `capacity_bytes`, only the length changes and nothing is
+    /// remapped, so shrinking keeps the capacity (use `release_from` to drop the tail pages).
+    /// Otherwise the backing file is extended when needed (but never truncated) and the mapping
+    /// is resized in place if possible (`mremap` with `MREMAP_MAYMOVE`), so no data is copied.
+    ///
+    /// Anonymous mappings can't be remapped and return `ErrorKind::Unsupported`.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn resize_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mut mapped_buf = MappedBuffer::new(buf)?;
+    ///     // SAFETY: All zero bytes are a valid `i32`, and the tempfile holds nothing else
+    ///     unsafe { mapped_buf.resize(32_000)? };
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// If the buffer grows, all zero bytes must be a valid `T` (see `anonymous`),
+    /// and so must the contents of the backing file past the mapping, if any.
+    #[cfg(target_os = "linux")]
+    pub unsafe fn resize(&mut self, new_len: usize) -> Result<(), Error> {
+        let Some(new_size) = self.offset.checked_add(byte_size::<T>(new_len)?) else {
+            return Err(Error::new(ErrorKind::InvalidInput, "Buffer size overflows usize"));
+        };
+        // Elements that fit into the mapping before it grows
+        let capacity = self.capacity_bytes() / size_of::<T>();
 
-    fn deref(&self) -> &Self::Target {
-        self.receive()
+        if new_size > self.size {
+            let Some(file) = &self.file else {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "Anonymous mappings can't be resized",
+                ));
+            };
+
+            let file_len = self.file_offset + new_size as u64;
+
+            if file.metadata()?.len() < file_len {
+                file.set_len(file_len)?;
+            }
+
+            // SAFETY: FFI. Valid ptr (*mut c_void) and size of the current mapping
+            // + processed `mremap` result
+            let ptr = unsafe {
+                libc::mremap(self.ptr, self.size, new_size, libc::MREMAP_MAYMOVE)
+            };
+
+            if ptr == libc::MAP_FAILED {
+                return Err(Error::last_os_error());
+            }
+
+            self.ptr = ptr;
+            self.size = new_size;
+        }
+
+        // Shrinking leaves the old elements in the mapping, so they are zeroed when added back
+        if new_len > self.len {
+            self.zero(self.len, new_len.min(capacity))?;
+        }
+
+        self.len = new_len;
+
+        Ok(())
     }
-}
 
-impl<T: Copy> Drop for MappedBuffer<T> {
-    fn drop(&mut self) {
-        // SAFETY: FFI. Valid ptr (*mut c_void) and size
+    /// Zeroes the elements `start..end`, which must be within the mapping,
+    /// making a read-only mapping writable in the meantime
+    #[cfg(target_os = "linux")]
+    fn zero(&mut self, start: usize, end: usize) -> Result<(), Error> {
+        if start >= end {
+            return Ok(());
+        }
+
+        if !self.writable {
+            self.protect(PROT_READ | PROT_WRITE)?;
+        }
+
+        // SAFETY: The elements `start..end` are within the mapping,
+        // which is valid for writing and properly aligned for type `T`
         unsafe {
-            munmap(self.ptr, self.size);
+            ptr::write_bytes(self.data().cast::<T>().add(start), 0, end - start);
+        }
+
+        match self.writable {
+            true => Ok(()),
+            false => self.protect(PROT_READ),
         }
     }
-}
\ No newline at end of file
+
+    /// If `zeroize` is `true`, the mapped buffer is overwritten with zeros
+    /// right before it is unmapped on drop, so sensitive data doesn't linger in memory.
+    ///
+    /// Combine it with `lock` to make sure the data is never written to the swap.
+    ///
+    /// The zeros are written through the mapping, so for a shared mapping of a file
+    /// that outlives the buffer (`from_path`, `from_file`, `map_region`, a builder `path`
+    /// or `memfd`) the file contents are overwritten as well, only within the mapped region.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn zeroize_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mut mapped_buf = MappedBuffer::new(buf)?;
+    ///     mapped_buf.set_zeroize(true);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_zeroize(&mut self, zeroize: bool) {
+        self.zeroize = zeroize;
+    }
+
+    /// If `make_readonly` is successful, the mapped buffer is protected against
+    /// further writes (`PROT_READ`), so a buffer can be filled once and then frozen.
+    /// Mutable access to it panics afterwards, until `make_writable` is called.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn make_readonly_example() -> Result<(), std::io::Error> {
+    ///     let mut mapped_buf = MappedBuffer::new([0u64; 16_000])?;
+    ///     mapped_buf.fill(69);
+    ///     mapped_buf.make_readonly()?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn make_readonly(&mut self) -> Result<(), Error> {
+        self.protect(PROT_READ)?;
+        self.writable = false;
+
+        Ok(())
+    }
+
+    /// If `make_writable` is successful, the mapped buffer can be written again
+    /// (`PROT_READ | PROT_WRITE`). Fails with `EACCES` if the backing file
+    /// isn't opened for writing.
+    pub fn make_writable(&mut self) -> Result<(), Error> {
+        self.protect(PROT_READ | PROT_WRITE)?;
+        self.writable = true;
+
+        Ok(())
+    }
+
+    /// Returns the number of `T` elements in the mapped buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of bytes mapped for the buffer, from its first element
+    /// to the end of the mapping. It is at least `len() * size_of::<T>()`,
+    /// and more if the mapping was rounded up (see `MappedBufferBuilder::round_to_pages`)
+    /// or the buffer was shrunk by `resize`, which can then grow into it without remapping.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::builder::MappedBufferBuilder;
+    /// use memguar::page_size;
+    ///
+    /// pub fn capacity_bytes_example() -> Result<(), std::io::Error> {
+    ///     let mapped_buf = MappedBufferBuilder::new()
+    ///         .round_to_pages(true)
+    ///         .build_from([0u8; 100])?;
+    ///     assert_eq!(mapped_buf.len(), 100);
+    ///     assert_eq!(mapped_buf.capacity_bytes(), page_size());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn capacity_bytes(&self) -> usize {
+        self.size - self.offset
+    }
+
+    /// Returns `true` if the mapped buffer holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the raw fd of the file backing the mapping, or `None` for anonymous mappings.
+    /// The file stays open for as long as the buffer, so the fd is valid (and not reused)
+    /// until it is dropped, but it must not be closed through the returned value.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::{ByteBuffer, MappedBuffer};
+    ///
+    /// pub fn as_raw_fd_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///     assert!(mapped_buf.as_raw_fd().is_some());
+    ///
+    ///     let anon_buf = ByteBuffer::new_bytes(16_000)?;
+    ///     assert!(anon_buf.as_raw_fd().is_none());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn as_raw_fd(&self) -> Option<RawFd> {
+        self.file.as_ref().map(File::as_raw_fd)
+    }
+
+    /// Borrows the fd of the file backing the mapping, or returns `None` for anonymous mappings,
+    /// so it can be passed to APIs taking `AsFd` (or duplicated with `try_clone_to_owned`).
+    /// `AsFd` itself isn't implemented, since it can't express a missing file.
+    /// # Examples
+    ///
+    /// ```
+    /// use std::fs::File;
+    ///
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn backing_fd_example() -> Result<(), std::io::Error> {
+    ///     let buf = [42u8; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///
+    ///     if let Some(fd) = mapped_buf.backing_fd() {
+    ///         let file = File::from(fd.try_clone_to_owned()?);
+    ///         assert_eq!(file.metadata()?.len(), 16_000);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn backing_fd(&self) -> Option<BorrowedFd<'_>> {
+        self.file.as_ref().map(File::as_fd)
+    }
+
+    /// If `flush` is successful, all changes of the mapped buffer are written back
+    /// to the backing file, blocking until the writeback is done (`MS_SYNC`).
+    pub fn flush(&self) -> Result<(), Error> {
+        self.sync(MS_SYNC)
+    }
+
+    /// If `flush_async` is successful, the writeback of the mapped buffer
+    /// is scheduled without waiting for it to be done (`MS_ASYNC`).
+    pub fn flush_async(&self) -> Result<(), Error> {
+        self.sync(MS_ASYNC)
+    }
+
+    /// If `sync_range` is successful, the changes to `len` elements starting at the element
+    /// `start` are written back to the backing file according to `mode`.
+    /// Only the pages covering the range are synced.
+    /// Returns `ErrorKind::InvalidInput` if the range is out of the buffer's bounds.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::{MappedBuffer, SyncMode};
+    ///
+    /// pub fn sync_range_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mut mapped_buf = MappedBuffer::new(buf)?;
+    ///     mapped_buf[8_000] = 69;
+    ///     mapped_buf.sync_range(8_000, 1, SyncMode::Sync)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn sync_range(&self, start: usize, len: usize, mode: SyncMode) -> Result<(), Error> {
+        let range = match start.checked_add(len) {
+            Some(end) if end <= self.len() => &self.receive()[start..end],
+            _ => return Err(Error::new(ErrorKind::InvalidInput, "Range is out of bounds")),
+        };
+
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        let addr = range.as_ptr() as usize;
+        let page_start = addr - addr % page_size();
+        let len = addr + size_of_val(range) - page_start;
+
+        // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the mapping
+        // + processed `msync` result
+        let result = unsafe {
+            msync(page_start as *mut c_void, len, mode as c_int)
+        };
+
+        match result {
+            0 => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    /// If `stats` is successful, it returns how many pages of the mapping are resident
+    /// in RAM (via `mincore`) and, on Linux, how many page faults the process had
+    /// since the buffer was mapped (from `/proc/self/stat`), if it was built
+    /// with `MappedBufferBuilder::track_faults`.
+    ///
+    /// The stats are computed on demand, and the faults are process-wide,
+    /// so they also count faults outside of the buffer.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::WillNeed;
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn stats_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///     mapped_buf.advise(WillNeed).unwrap();
+    ///     let stats = mapped_buf.stats()?;
+    ///     println!("{}/{} pages resident", stats.resident_pages, stats.total_pages);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn stats(&self) -> Result<MappingStats, Error> {
+        let mut pages = vec![0u8; self.size.div_ceil(page_size())];
+        // SAFETY: FFI. Valid page aligned ptr (*mut c_void) and size, `pages` holds
+        // one byte per page + processed `mincore` result
+        let result = unsafe {
+            mincore(self.ptr, self.size, pages.as_mut_ptr().cast())
+        };
+
+        if result != 0 {
+            return Err(Error::last_os_error());
+        }
+
+        let faults = self
+            .faults
+            .zip(page_faults())
+            .map(|((minor, major), (minor_now, major_now))| {
+                (minor_now.saturating_sub(minor), major_now.saturating_sub(major))
+            });
+
+        Ok(MappingStats {
+            resident_pages: pages.iter().filter(|&&page| page & 1 == 1).count(),
+            total_pages: pages.len(),
+            minor_faults: faults.map(|(minor, _)| minor),
+            major_faults: faults.map(|(_, major)| major),
+        })
+    }
+
+    fn sync(&self, flags: c_int) -> Result<(), Error> {
+        // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `msync` result
+        let result = unsafe {
+            msync(self.ptr, self.size, flags)
+        };
+
+        match result {
+            0 => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    fn protect(&self, prot: c_int) -> Result<(), Error> {
+        // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `mprotect` result
+        let result = unsafe {
+            mprotect(self.ptr, self.size, prot)
+        };
+
+        match result {
+            0 => Ok(()),
+            _ => Err(Error::last_os_error()),
+        }
+    }
+
+    /// Copies the mapped buffer into an owned `Vec` and unmaps it.
+    pub fn into_vec(self) -> Vec<T> {
+        self.receive().to_vec()
+    }
+
+    /// Creates an independent copy of the mapped buffer, backed by a new tempfile.
+    ///
+    /// The clone does not alias the original's pages, writes to either buffer
+    /// are not visible through the other. Writability and `set_zeroize` are kept.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn try_clone_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///     let mut snapshot = mapped_buf.try_clone()?;
+    ///     snapshot[0] = 69;
+    ///     assert_eq!(mapped_buf[0], 420);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        let mut mapped_buf = match self.len {
+            // `build_from` rejects empty buffers, which `with_capacity` can create
+            0 => {
+                // SAFETY: An empty buffer holds no zeroed elements
+                let mut mapped_buf = unsafe { Self::with_capacity(0)? };
+
+                if !self.writable {
+                    mapped_buf.make_readonly()?;
+                }
+
+                mapped_buf
+            }
+            _ => MappedBufferBuilder::new()
+                .writable(self.writable)
+                .build_from(self.receive())?,
+        };
+        mapped_buf.zeroize = self.zeroize;
+
+        Ok(mapped_buf)
+    }
+
+    /// Sets every element of the mapped buffer to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mapping is read-only.
+    pub fn fill(&mut self, value: T) {
+        self.receive_mut().fill(value);
+    }
+
+    /// Copies all elements of `src` into the mapped buffer.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn copy_from_slice_example() -> Result<(), std::io::Error> {
+    ///     let mut mapped_buf = MappedBuffer::new([0u64; 4])?;
+    ///     mapped_buf.copy_from_slice(&[4, 2, 0, 69]);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` has a different length, or if the mapping is read-only.
+    pub fn copy_from_slice(&mut self, src: &[T]) {
+        self.receive_mut().copy_from_slice(src);
+    }
+
+    /// Returns a copy of the element at `index`, without materializing the whole slice.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn read_at_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///     assert_eq!(mapped_buf.read_at(15_999), 420);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, like `Index`.
+    pub fn read_at(&self, index: usize) -> T {
+        let len = self.len();
+        assert!(index < len, "index out of bounds: the len is {len} but the index is {index}");
+
+        // SAFETY: `index` is within the mapping, which is valid for reading
+        // and properly aligned for type `T`
+        unsafe {
+            self.data().cast::<T>().add(index).read()
+        }
+    }
+
+    /// Overwrites the element at `index` with `value`, without materializing the whole slice.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn write_at_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mut mapped_buf = MappedBuffer::new(buf)?;
+    ///     mapped_buf.write_at(0, 69);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, like `IndexMut`, or if the mapping is read-only.
+    pub fn write_at(&mut self, index: usize, value: T) {
+        assert!(self.writable, "Read-only mapping");
+        let len = self.len();
+        assert!(index < len, "index out of bounds: the len is {len} but the index is {index}");
+
+        // SAFETY: `index` is within the mapping, which is valid for writing,
+        // properly aligned for type `T` and uniquely borrowed via `&mut self`
+        unsafe {
+            self.data().cast::<T>().add(index).write(value);
+        }
+    }
+
+    /// Returns an iterator over the mapped elements.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.receive().iter()
+    }
+
+    /// Returns a reference to an element or subslice, or `None` if out of bounds.
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.receive().get(index)
+    }
+
+    /// Returns a mutable reference to an element or subslice, or `None` if out of bounds.
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.receive_mut().get_mut(index)
+    }
+
+    /// Divides the mapped buffer into two slices at `mid`, the first one holding
+    /// the elements `[0, mid)` and the second one the elements `[mid, len)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (&[T], &[T]) {
+        self.receive().split_at(mid)
+    }
+
+    /// Divides the mapped buffer into two non-overlapping mutable slices at `mid`,
+    /// that can be handed to different threads.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn split_at_mut_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mut mapped_buf = MappedBuffer::new(buf)?;
+    ///     let (left, right) = mapped_buf.split_at_mut(8_000);
+    ///
+    ///     std::thread::scope(|scope| {
+    ///         scope.spawn(|| left.fill(4));
+    ///         scope.spawn(|| right.fill(2));
+    ///     });
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`, or if the mapping is read-only.
+    pub fn split_at_mut(&mut self, mid: usize) -> (&mut [T], &mut [T]) {
+        self.receive_mut().split_at_mut(mid)
+    }
+
+    /// Returns a raw pointer to the first element of the mapped buffer.
+    pub fn as_ptr(&self) -> *const T {
+        self.data().cast()
+    }
+
+    /// Returns a raw mutable pointer to the first element of the mapped buffer.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.data().cast()
+    }
+
+    /// Returns the page aligned address the mapping starts at, as listed in `/proc/self/maps`.
+    /// It is the address of the first element, unless the buffer was created by `map_region`
+    /// with an offset that isn't page aligned.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    /// use memguar::page_size;
+    ///
+    /// pub fn addr_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///     assert_eq!(mapped_buf.addr() % page_size(), 0);
+    ///     assert_eq!(format!("{mapped_buf:p}"), format!("{:#x}", mapped_buf.addr()));
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn addr(&self) -> usize {
+        self.ptr as usize
+    }
+
+    /// Returns the mapped buffer as raw bytes.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not have padding bytes (or other uninitialized bytes), since they would be
+    /// exposed as initialized `u8`s. Integers, floats and arrays of them are fine,
+    /// structs with fields of different sizes usually aren't.
+    pub unsafe fn as_bytes(&self) -> &[u8] {
+        // SAFETY: The pointer `self.data()` is valid for reading `self.len * size_of::<T>()` bytes,
+        // which are initialized by the caller's contract
+        unsafe {
+            std::slice::from_raw_parts(self.data().cast(), self.len * size_of::<T>())
+        }
+    }
+
+    /// Pointer to the first element
+    fn data(&self) -> *mut c_void {
+        // SAFETY: `self.offset` is within the mapping
+        unsafe {
+            self.ptr.byte_add(self.offset)
+        }
+    }
+
+    /// If `advise` is successful, it allows the system to apply specific optimizations
+    /// to the mapped pages, based on the specified flag.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::WillNeed;
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// let buf = [420; 16_000];
+    /// let mapped_buf = MappedBuffer::new(buf).unwrap();
+    ///
+    /// mapped_buf
+    ///     .advise(WillNeed)
+    ///     .unwrap();
+    /// ```
+    pub fn advise(&self, advise: Advise) -> Result<(), AdviseError> {
+        // SAFETY: FFI. Valid page aligned ptr (*mut c_void) and size + processed `posix_madvise` result
+        let result = unsafe {
+            posix_madvise(self.ptr, self.size, advise as c_int)
+        };
+        let result = match result {
+            0 => Ok(()),
+            result => Err(AdviseError::from(result)),
+        };
+
+        trace_syscall!(
+            "posix_madvise",
+            &result,
+            ptr = ?self.ptr,
+            len = self.size,
+            advise = ?advise
+        );
+        result
+    }
+
+    /// If `prefetch` is successful, the pages holding the elements in `range` are resident.
+    /// It advises `WillNeed` for the pages and then reads one byte of each page,
+    /// so unlike the bare hint it blocks until every page has been faulted in,
+    /// which may take a while for large ranges of a file on disk.
+    /// Returns `EINVAL` if the range is out of the buffer's bounds.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// let buf = [420; 16_000];
+    /// let mapped_buf = MappedBuffer::new(buf).unwrap();
+    ///
+    /// mapped_buf
+    ///     .prefetch(4_000..8_000)
+    ///     .unwrap();
+    /// ```
+    pub fn prefetch<R: RangeBounds<usize>>(&self, range: R) -> Result<(), AdviseError> {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        let range = self.receive().get(bounds).ok_or(AdviseError::EINVAL)?;
+
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        advise_pages(range, Advise::WillNeed)?;
+
+        let page_size = page_size();
+        let addr = range.as_ptr() as usize;
+        let start = addr - addr % page_size;
+        let end = addr + size_of_val(range);
+
+        for page in (start..end).step_by(page_size) {
+            // SAFETY: `page` is within the mapping, which is valid for reading.
+            // Volatile, so the read isn't elided
+            unsafe {
+                ptr::read_volatile(page as *const u8);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// If `prefetch_async` is successful, reading the pages holding the elements in `range`
+    /// has been started without waiting for it. It advises `WillNeed` for the pages and,
+    /// for file-backed mappings on Linux, also starts `readahead` of the matching part of the file,
+    /// which is a stronger hint for data on disk. Anonymous mappings only get the hint.
+    /// Returns `EINVAL` if the range is out of the buffer's bounds.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// let buf = [420; 16_000];
+    /// let mapped_buf = MappedBuffer::new(buf).unwrap();
+    ///
+    /// mapped_buf
+    ///     .prefetch_async(4_000..8_000)
+    ///     .unwrap();
+    /// ```
+    pub fn prefetch_async<R: RangeBounds<usize>>(&self, range: R) -> Result<(), AdviseError> {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        let range = self.receive().get(bounds).ok_or(AdviseError::EINVAL)?;
+
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        advise_pages(range, Advise::WillNeed)?;
+
+        #[cfg(target_os = "linux")]
+        if let Some(file) = &self.file {
+            // The mapping starts at `self.file_offset` in the file
+            let offset = self.file_offset + (range.as_ptr() as usize - self.ptr as usize) as u64;
+            let offset = off_t::try_from(offset).map_err(|_| AdviseError::EINVAL)?;
+            let len = size_of_val(range);
+            // SAFETY: FFI. Valid fd of the backing file, offset and len within it
+            // + processed `readahead` result
+            let result = unsafe {
+                libc::readahead(file.as_raw_fd(), offset, len)
+            };
+            let result = match result {
+                0 => Ok(()),
+                _ => Err(AdviseError::from(Errno::last().raw())),
+            };
+
+            trace_syscall!("readahead", &result, fd = file.as_raw_fd(), offset, len);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns an iterator over `chunk_len` element chunks of the mapped buffer
+    /// (the last one may be shorter), that advises `WillNeed` for each chunk before
+    /// yielding it and `DontNeed` for it once the iterator advances past it.
+    /// Both go through `posix_madvise` and are only hints: glibc and musl ignore
+    /// `POSIX_MADV_DONTNEED`, so on Linux the chunks already read stay in RAM
+    /// until the kernel reclaims them under memory pressure.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn advised_chunks_example() -> Result<(), memguar::advisor::AdviseError> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf).unwrap();
+    ///
+    ///     for chunk in mapped_buf.advised_chunks(4_000) {
+    ///         let _sum = chunk?.iter().sum::<i32>();
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is zero.
+    pub fn advised_chunks(&self, chunk_len: usize) -> AdvisedChunks<'_, T> {
+        assert!(chunk_len > 0, "Zero size chunk");
+
+        AdvisedChunks {
+            chunks: self.receive().chunks(chunk_len),
+            current: None,
+        }
+    }
+
+    /// If `release_from` is successful, the pages from the element `start` to the end
+    /// of the buffer are released (`MADV_DONTNEED`), relieving memory pressure once
+    /// the buffer is partly processed.
+    ///
+    /// Shared pages keep their contents and are faulted back in on the next access,
+    /// while private ones may be zero-filled (as on Linux), hence `&mut self`. The page containing
+    /// `start` is only released if `start` is at its beginning, so elements before `start`
+    /// are never lost. Returns `EINVAL` if `start` is out of the buffer's bounds.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// let buf = [420; 16_000];
+    /// let mut mapped_buf = MappedBuffer::new(buf).unwrap();
+    ///
+    /// // SAFETY: The mapping is shared, so the released elements keep their values
+    /// unsafe { mapped_buf.release_from(8_000) }
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// Unless the mapping is shared, the released elements may read back as zero bytes,
+    /// so all zero bytes must be a valid `T` (see `anonymous`) for private mappings,
+    /// such as `new_private` or a builder with `shared(false)`.
+    pub unsafe fn release_from(&mut self, start: usize) -> Result<(), AdviseError> {
+        let range = self.receive().get(start..).ok_or(AdviseError::EINVAL)?;
+        let addr = (range.as_ptr() as usize).next_multiple_of(page_size());
+        let end = self.ptr as usize + self.size;
+
+        if addr >= end {
+            return Ok(());
+        }
+
+        // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the mapping
+        // + processed `madvise` result
+        let result = unsafe {
+            madvise(addr as *mut c_void, end - addr, MADV_DONTNEED)
+        };
+        let result = match result {
+            0 => Ok(()),
+            _ => Err(AdviseError::from(Errno::last().raw())),
+        };
+
+        trace_syscall!("madvise", &result, ptr = addr, len = end - addr, advise = "MADV_DONTNEED");
+        result
+    }
+
+    /// If `lock` is successful, the mapped pages are locked,
+    /// preventing them from being swapped out to disk/swap-zone.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// let buf = [420; 16_000];
+    /// let mapped_buf = MappedBuffer::new(buf).unwrap();
+    ///
+    /// mapped_buf
+    ///     .lock()
+    ///     .unwrap();
+    /// ```
+    pub fn lock(&self) -> Result<(), LockError> {
+        // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `mlock` result
+        let result = unsafe {
+            mlock(self.ptr, self.size)
+        };
+        let result = match result {
+            0 => Ok(()),
+            _ => Err(LockError::last_lock_error(self.size)),
+        };
+
+        trace_syscall!("mlock", &result, ptr = ?self.ptr, len = self.size);
+        result
+    }
+
+    /// If `lock_on_fault` is successful, the mapped pages are locked as they are faulted in
+    /// (`mlock2` with `MLOCK_ONFAULT`), so pages that are never touched don't take up RAM.
+    /// Returns `ENOSYS` on platforms without `mlock2`.
+    pub fn lock_on_fault(&self) -> Result<(), LockError> {
+        #[cfg(target_os = "linux")]
+        {
+            // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `mlock2` result
+            let result = unsafe {
+                libc::mlock2(self.ptr, self.size, libc::MLOCK_ONFAULT as _)
+            };
+            let result = match result {
+                0 => Ok(()),
+                _ => Err(LockError::last_lock_error(self.size)),
+            };
+
+            trace_syscall!(
+                "mlock2",
+                &result,
+                ptr = ?self.ptr,
+                len = self.size,
+                flags = "MLOCK_ONFAULT"
+            );
+            result
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        Err(LockError::ENOSYS)
+    }
+
+    /// If `unlock` is successful, the mapped pages are unlocked.
+    pub fn unlock(&self) -> Result<(), LockError> {
+        // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `munlock` result
+        let result = unsafe {
+            munlock(self.ptr, self.size)
+        };
+        let result = match result {
+            0 => Ok(()),
+            _ => Err(LockError::last_os_error()),
+        };
+
+        trace_syscall!("munlock", &result, ptr = ?self.ptr, len = self.size);
+        result
+    }
+
+    /// If `receive` is successful, It returns a slice that represents the mapped buffer.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn receive_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///     let _buf = mapped_buf.receive();
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn receive(&self) -> &[T] {
+        // SAFETY: The pointer `self.data()` is valid for
+        // reading `self.len()` elements and that these bytes are properly aligned for type `T`.
+        unsafe {
+            std::slice::from_raw_parts(self.data().cast(), self.len())
+        }
+    }
+
+    /// If `receive_mut` is successful, It returns a mutable slice that represents the mapped buffer.
+    /// Writes go straight to the shared mapping.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn receive_mut_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mut mapped_buf = MappedBuffer::new(buf)?;
+    ///     mapped_buf.receive_mut()[0] = 69;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the mapping is read-only.
+    pub fn receive_mut(&mut self) -> &mut [T] {
+        assert!(self.writable, "Read-only mapping");
+        // SAFETY: The pointer `self.data()` is valid for reading and writing
+        // `self.len()` elements, properly aligned for type `T` and uniquely borrowed via `&mut self`.
+        unsafe {
+            std::slice::from_raw_parts_mut(self.data().cast(), self.len())
+        }
+    }
+}
+
+// SAFETY: `MappedBuffer` exclusively owns its mapping, `ptr` is never aliased by another
+// `MappedBuffer` and the mapping stays valid until drop, so moving it between threads
+// is like moving a `Box<[T]>`
+unsafe impl<T: Copy + Send> Send for MappedBuffer<T> {}
+
+// SAFETY: Shared access only hands out `&[T]`, mutation requires `&mut self`,
+// so sharing `&MappedBuffer` between threads is like sharing `&[T]`
+unsafe impl<T: Copy + Sync> Sync for MappedBuffer<T> {}
+
+impl<T: Copy> Clone for MappedBuffer<T> {
+    /// Same as `try_clone`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new mapping can't be created.
+    fn clone(&self) -> Self {
+        self.try_clone()
+            .expect("Failed to map the clone")
+    }
+}
+
+/// Number of elements printed by `Debug`, unless the alternate (`{:#?}`) formatter is used
+const DEBUG_ELEMENTS: usize = 8;
+
+impl<T: Copy + Debug> Debug for MappedBuffer<T> {
+    /// Prints the size, element count, address and the first elements of the mapped buffer.
+    /// The alternate (`{:#?}`) formatter prints all elements.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        struct Elements<'a, T>(&'a [T]);
+
+        impl<T: Debug> Debug for Elements<'_, T> {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                match f.alternate() || self.0.len() <= DEBUG_ELEMENTS {
+                    true => f.debug_list().entries(self.0).finish(),
+                    false => f
+                        .debug_list()
+                        .entries(&self.0[..DEBUG_ELEMENTS])
+                        .finish_non_exhaustive(),
+                }
+            }
+        }
+
+        f.debug_struct("MappedBuffer")
+            .field("size", &self.size)
+            .field("len", &self.len())
+            .field("ptr", &self.as_ptr())
+            .field("elements", &Elements(self.receive()))
+            .finish()
+    }
+}
+
+impl<T: Copy> fmt::Pointer for MappedBuffer<T> {
+    /// Prints the address the mapping starts at, same as `addr`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.ptr, f)
+    }
+}
+
+impl<T: Copy> Deref for MappedBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.receive()
+    }
+}
+
+impl<T: Copy> DerefMut for MappedBuffer<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.receive_mut()
+    }
+}
+
+impl<T: Copy> TryFrom<&[T]> for MappedBuffer<T> {
+    type Error = Error;
+
+    /// Same as `MappedBuffer::new`.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn try_from_example() -> Result<(), std::io::Error> {
+    ///     let buf = vec![420; 16_000];
+    ///     let _mapped_buf: MappedBuffer<_> = buf.as_slice().try_into()?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    fn try_from(buf: &[T]) -> Result<Self, Self::Error> {
+        Self::new(buf)
+    }
+}
+
+impl<T: Copy> TryFrom<Vec<T>> for MappedBuffer<T> {
+    type Error = Error;
+
+    /// Same as `MappedBuffer::new`, the `Vec` is dropped once its elements are copied.
+    fn try_from(buf: Vec<T>) -> Result<Self, Self::Error> {
+        Self::new(buf)
+    }
+}
+
+impl<T: Copy> AsRef<[T]> for MappedBuffer<T> {
+    fn as_ref(&self) -> &[T] {
+        self.receive()
+    }
+}
+
+impl<T: Copy> AsMut<[T]> for MappedBuffer<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.receive_mut()
+    }
+}
+
+impl<T: Copy + PartialEq> PartialEq for MappedBuffer<T> {
+    /// Compares the mapped contents, not the mappings.
+    fn eq(&self, other: &Self) -> bool {
+        self.receive() == other.receive()
+    }
+}
+
+impl<T: Copy + Eq> Eq for MappedBuffer<T> {}
+
+impl<T: Copy + PartialEq> PartialEq<[T]> for MappedBuffer<T> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.receive() == other
+    }
+}
+
+impl<T: Copy + Hash> Hash for MappedBuffer<T> {
+    /// Hashes the mapped contents like the slice `[T]`, consistently with `PartialEq`.
+    /// Every element is read, so the whole mapping is faulted in,
+    /// which is expensive for large buffers.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.receive().hash(state);
+    }
+}
+
+impl<T: Copy> IntoIterator for MappedBuffer<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Consumes the mapped buffer into an iterator that yields the elements by value
+    /// and unmaps the buffer when it drops.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn into_iter_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420; 16_000];
+    ///     let mapped_buf = MappedBuffer::new(buf)?;
+    ///
+    ///     for element in mapped_buf {
+    ///         assert_eq!(element, 420);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            end: self.len(),
+            buf: self,
+            start: 0,
+        }
+    }
+}
+
+impl<'a, T: Copy> IntoIterator for &'a MappedBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A by-value iterator over the elements of a `MappedBuffer`,
+/// created by `MappedBuffer::into_iter`. The buffer is unmapped when the iterator drops.
+pub struct IntoIter<T: Copy> {
+    buf: MappedBuffer<T>,
+    start: usize,
+    end: usize,
+}
+
+impl<T: Copy> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.start += 1;
+        Some(self.buf[self.start - 1])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T: Copy> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        Some(self.buf[self.end])
+    }
+}
+
+impl<T: Copy> ExactSizeIterator for IntoIter<T> {}
+
+#[cfg(feature = "bytes")]
+impl<T: Copy + Send + Sync + 'static> MappedBuffer<T> {
+    /// Converts the mapped buffer into `bytes::Bytes` without copying.
+    /// The mapping is owned by the returned `Bytes` and is unmapped when its last clone drops.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn into_bytes_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420u32; 16_000];
+    ///     // SAFETY: `u32` has no padding bytes
+    ///     let bytes = unsafe { MappedBuffer::new(buf)?.into_bytes() };
+    ///     assert_eq!(bytes.len(), 64_000);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// Same as `as_bytes`, `T` must not have padding bytes.
+    pub unsafe fn into_bytes(self) -> bytes::Bytes {
+        bytes::Bytes::from_owner(BytesOwner(self))
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> MappedBuffer<T> {
+    /// Same as `new`, restricted to `Pod` element types, for which any bit pattern is valid.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::MappedBuffer;
+    ///
+    /// pub fn new_pod_example() -> Result<(), std::io::Error> {
+    ///     let buf = [420u32; 16_000];
+    ///     let _mapped_buf = MappedBuffer::new_pod(buf)?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_pod<B: AsRef<[T]>>(buf: B) -> Result<Self, Error> {
+        Self::new(buf)
+    }
+
+    /// Same as `anonymous`, restricted to `Pod` element types, for which all zero bytes are valid.
+    pub fn anonymous_pod(len: usize) -> Result<Self, Error> {
+        // SAFETY: `Pod` types are `Zeroable`
+        unsafe { Self::anonymous(len) }
+    }
+
+    /// Same as `from_file`, restricted to `Pod` element types. Unlike with any `Copy` type,
+    /// reading a file whose contents weren't written by this process is sound,
+    /// since any bit pattern is a valid `T`.
+    pub fn from_file_pod(file: File, len: usize) -> Result<Self, Error> {
+        // SAFETY: Any bytes are a valid `Pod`
+        unsafe { Self::from_file(file, len) }
+    }
+
+    /// Same as `from_path`, restricted to `Pod` element types, see `from_file_pod`.
+    pub fn from_path_pod<P: AsRef<Path>>(path: P, len: usize) -> Result<Self, Error> {
+        // SAFETY: Any bytes are a valid `Pod`
+        unsafe { Self::from_path(path, len) }
+    }
+
+    /// Same as `map_region`, restricted to `Pod` element types, see `from_file_pod`.
+    pub fn map_region_pod<P: AsRef<Path>>(path: P, offset: u64, len: usize) -> Result<Self, Error> {
+        // SAFETY: Any bytes are a valid `Pod`
+        unsafe { Self::map_region(path, offset, len) }
+    }
+}
+
+/// Owner of the mapping handed to `bytes::Bytes::from_owner`,
+/// only created by `into_bytes` for element types without padding bytes
+#[cfg(feature = "bytes")]
+struct BytesOwner<T: Copy>(MappedBuffer<T>);
+
+#[cfg(feature = "bytes")]
+impl<T: Copy> AsRef<[u8]> for BytesOwner<T> {
+    fn as_ref(&self) -> &[u8] {
+        // SAFETY: `T` has no padding bytes, by the contract of `into_bytes`
+        unsafe { self.0.as_bytes() }
+    }
+}
+
+/// Locking of the pages of a `MappedBuffer` created by `MappedBuffer::new_with`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockPolicy {
+    /// Pages are not locked
+    None,
+    /// All pages are locked right away (`mlock`)
+    Eager,
+    /// Pages are locked as they are faulted in (`mlock2` with `MLOCK_ONFAULT`), Linux only
+    OnFault,
+}
+
+/// Errors of `MappedBuffer::new_with`, from either mapping the buffer or locking its pages
+#[derive(Debug)]
+pub enum MapError {
+    Map(Error),
+    Lock(LockError),
+}
+
+impl From<Error> for MapError {
+    fn from(err: Error) -> Self {
+        MapError::Map(err)
+    }
+}
+
+impl From<LockError> for MapError {
+    fn from(err: LockError) -> Self {
+        MapError::Lock(err)
+    }
+}
+
+impl Display for MapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Map(err) => write!(f, "cannot map buffer: {err}"),
+            MapError::Lock(err) => write!(f, "cannot lock buffer: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for MapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MapError::Map(err) => Some(err),
+            MapError::Lock(err) => Some(err),
+        }
+    }
+}
+
+/// Residency and page fault stats of a `MappedBuffer`, returned by `MappedBuffer::stats`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MappingStats {
+    /// Pages of the mapping that are resident in RAM
+    pub resident_pages: usize,
+    /// All pages of the mapping
+    pub total_pages: usize,
+    /// Minor page faults of the process since the buffer was mapped, if tracked, Linux only
+    pub minor_faults: Option<u64>,
+    /// Major page faults (that needed I/O) of the process since the buffer was mapped,
+    /// if tracked, Linux only
+    pub major_faults: Option<u64>,
+}
+
+/// Combinable seals for `MappedBuffer::seal`
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Seals(c_int);
+
+#[cfg(target_os = "linux")]
+impl Seals {
+    /// The file can't be shrunk (`F_SEAL_SHRINK`)
+    pub const SHRINK: Self = Self(libc::F_SEAL_SHRINK);
+    /// The file can't be grown (`F_SEAL_GROW`)
+    pub const GROW: Self = Self(libc::F_SEAL_GROW);
+    /// The contents of the file can't be modified (`F_SEAL_WRITE`)
+    pub const WRITE: Self = Self(libc::F_SEAL_WRITE);
+    /// Existing writable mappings stay writable, but no new ones can be created
+    /// and the file can't be written (`F_SEAL_FUTURE_WRITE`)
+    pub const FUTURE_WRITE: Self = Self(libc::F_SEAL_FUTURE_WRITE);
+    /// No more seals can be added (`F_SEAL_SEAL`)
+    pub const SEAL: Self = Self(libc::F_SEAL_SEAL);
+}
+
+#[cfg(target_os = "linux")]
+impl BitOr for Seals {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Write-back modes for `MappedBuffer::sync_range`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum SyncMode {
+    /// Block until the writeback is done (`MS_SYNC`)
+    Sync = MS_SYNC,
+    /// Schedule the writeback without waiting for it (`MS_ASYNC`)
+    Async = MS_ASYNC,
+    /// Invalidate other mappings of the file, so they see the written data (`MS_INVALIDATE`)
+    Invalidate = MS_INVALIDATE,
+}
+
+/// An iterator over the chunks of a `MappedBuffer` that advises each chunk as it advances,
+/// created by `MappedBuffer::advised_chunks`.
+pub struct AdvisedChunks<'a, T> {
+    chunks: Chunks<'a, T>,
+    current: Option<&'a [T]>,
+}
+
+impl<'a, T> Iterator for AdvisedChunks<'a, T> {
+    type Item = Result<&'a [T], AdviseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(previous) = self.current.take() {
+            if let Err(err) = advise_pages(previous, Advise::DontNeed) {
+                return Some(Err(err));
+            }
+        }
+
+        let chunk = self.chunks.next()?;
+        self.current = Some(chunk);
+
+        Some(advise_pages(chunk, Advise::WillNeed).map(|()| chunk))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+/// A `MappedBuffer` of raw bytes
+pub type ByteBuffer = MappedBuffer<u8>;
+
+impl MappedBuffer<u8> {
+    /// Creates a zero-initialized anonymous buffer of `len` bytes.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::mapper::ByteBuffer;
+    ///
+    /// pub fn new_bytes_example() -> Result<(), std::io::Error> {
+    ///     let mut mapped_buf = ByteBuffer::new_bytes(16_000)?;
+    ///     mapped_buf[..5].copy_from_slice(b"bytes");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new_bytes(len: usize) -> Result<Self, Error> {
+        // SAFETY: All zero bytes are a valid `u8`
+        unsafe { Self::anonymous(len) }
+    }
+}
+
+/// A read-only `MappedBuffer`, created by `MappedBuffer::new_readonly`.
+pub struct ReadOnlyMappedBuffer<T: Copy>(MappedBuffer<T>);
+
+impl<T: Copy> ReadOnlyMappedBuffer<T> {
+    /// Returns the number of `T` elements in the mapped buffer.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the mapped buffer holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// If `receive` is successful, It returns a slice that represents the mapped buffer.
+    pub fn receive(&self) -> &[T] {
+        self.0.receive()
+    }
+}
+
+impl<T: Copy> Deref for ReadOnlyMappedBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.receive()
+    }
+}
+
+impl<T: Copy, I: SliceIndex<[T]>> Index<I> for MappedBuffer<T> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.receive()[index]
+    }
+}
+
+impl<T: Copy, I: SliceIndex<[T]>> IndexMut<I> for MappedBuffer<T> {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut self.receive_mut()[index]
+    }
+}
+
+impl<T: Copy> Drop for MappedBuffer<T> {
+    fn drop(&mut self) {
+        if self.zeroize {
+            // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `mprotect` result
+            let writable = self.writable || unsafe {
+                mprotect(self.ptr, self.size, PROT_READ | PROT_WRITE) == 0
+            };
+
+            if writable {
+                let ptr = self.data().cast::<u8>();
+
+                for offset in 0..self.size - self.offset {
+                    // SAFETY: `offset` is within the mapping, which is valid for writing.
+                    // Volatile, so the writes aren't elided before `munmap`
+                    unsafe {
+                        ptr::write_volatile(ptr.add(offset), 0);
+                    }
+                }
+            }
+        }
+
+        // SAFETY: FFI. Valid ptr (*mut c_void) and size + processed `munmap` result
+        let result = unsafe {
+            munmap(self.ptr, self.size)
+        };
+
+        // Unmap errors can't be reported from `drop`, only traced
+        trace_syscall!(
+            "munmap",
+            match result {
+                0 => Ok(()),
+                _ => Err(Error::last_os_error()),
+            },
+            ptr = ?self.ptr,
+            len = self.size
+        );
+    }
+}
+
+pub(crate) fn map(
+    size: usize,
+    prot: c_int,
+    flags: c_int,
+    fd: c_int,
+    offset: off_t,
+) -> Result<*mut c_void, Error> {
+    // SAFETY: FFI. Safe cast (`size as size_t` = usize as usize)
+    // Valid raw file descriptor (or `-1` for anonymous mapping), page aligned offset
+    // + processed `mmap` result
+    let ptr = unsafe {
+        mmap(
+            ptr::null_mut(),
+            size as size_t,
+            prot,
+            flags,
+            fd,
+            offset,
+        )
+    };
+
+    let result = match ptr {
+        libc::MAP_FAILED => {
+            let err = Error::last_os_error();
+            let name = err
+                .raw_os_error()
+                .and_then(|err| Errno::from(err).name());
+
+            match name {
+                Some(name) => {
+                    Err(Error::new(err.kind(), format!("mmap failed with {name}: {err}")))
+                }
+                None => Err(err),
+            }
+        }
+        ptr => Ok(ptr),
+    };
+
+    trace_syscall!("mmap", &result, len = size, prot, flags, fd, offset);
+    result
+}
+
+/// Advises the pages holding the non-empty `range` of a mapping,
+/// starting at the page containing its first element.
+fn advise_pages<T>(range: &[T], advise: Advise) -> Result<(), AdviseError> {
+    let addr = range.as_ptr() as usize;
+    let start = addr - addr % page_size();
+    let end = addr + size_of_val(range);
+    // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the mapping
+    // + processed `posix_madvise` result
+    let result = unsafe {
+        posix_madvise(start as *mut c_void, end - start, advise as c_int)
+    };
+    let result = match result {
+        0 => Ok(()),
+        result => Err(AdviseError::from(result)),
+    };
+
+    trace_syscall!("posix_madvise", &result, ptr = start, len = end - start, advise = ?advise);
+    result
+}
+
+/// Size of `len` elements of `T` in bytes.
+/// Returns `InvalidInput` if it is zero or overflows `usize`, which is easy on 32-bit targets,
+/// or if `T` is aligned beyond the page size, since mappings are only page aligned.
+pub(crate) fn byte_size<T>(len: usize) -> Result<usize, Error> {
+    if align_of::<T>() > page_size() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Element alignment exceeds the page size",
+        ));
+    }
+
+    match len.checked_mul(size_of::<T>()) {
+        Some(0) => Err(Error::new(ErrorKind::InvalidInput, "Zero size buffer")),
+        Some(size) if size <= isize::MAX as usize => Ok(size),
+        _ => Err(Error::new(ErrorKind::InvalidInput, "Buffer size overflows usize")),
+    }
+}
+
+/// Minor and major page faults of the process, from `/proc/self/stat`
+#[cfg(target_os = "linux")]
+pub(crate) fn page_faults() -> Option<(u64, u64)> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // The command name may contain spaces, so the fields are counted after it
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace();
+    let minor = fields.nth(7)?.parse().ok()?;
+    let major = fields.nth(1)?.parse().ok()?;
+
+    Some((minor, major))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn page_faults() -> Option<(u64, u64)> {
+    None
+}
+
+/// Default huge page size, from `Hugepagesize` in `/proc/meminfo`
+#[cfg(target_os = "linux")]
+fn huge_page_size() -> Result<usize, Error> {
+    std::fs::read_to_string("/proc/meminfo")?
+        .lines()
+        .find_map(|line| line.strip_prefix("Hugepagesize:"))
+        .and_then(|size| size.trim().strip_suffix("kB"))
+        .and_then(|size| size.trim().parse::<usize>().ok())
+        .map(|size| size * 1024)
+        .ok_or_else(|| Error::new(ErrorKind::Unsupported, "Huge pages are unavailable"))
+}
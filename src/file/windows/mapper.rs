@@ -0,0 +1,217 @@
+use std::ffi::c_void;
+use std::fmt::{self, Formatter};
+use std::fs::File;
+use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::os::windows::io::AsRawHandle;
+use std::path::Path;
+use std::ptr;
+
+use tempfile::{tempfile, tempfile_in};
+use windows_sys::Win32::Foundation::CloseHandle;
+use windows_sys::Win32::System::Memory::{
+    CreateFileMappingW, FILE_MAP_ALL_ACCESS, MapViewOfFile, MEMORY_MAPPED_VIEW_ADDRESS,
+    PAGE_READWRITE, UnmapViewOfFile,
+};
+
+use crate::page::page_size;
+
+/// A struct that represents a buffer that is mapped to memory.
+///
+/// The `MappedBuffer` struct provides a safe and convenient way to create a buffer that is mapped to memory,
+/// allowing you to read and write data to the buffer using a slice-like interface.
+///
+/// Mappings are page aligned, so the alignment of `T` must not exceed the page size,
+/// otherwise `new` returns `ErrorKind::InvalidInput`.
+/// # Examples
+///
+/// ```
+/// use memguar::mapper::MappedBuffer;
+///
+/// pub fn map_example() -> Result<(), std::io::Error> {
+///     let buf = [420; 16_000];
+///     let mapped_buf = MappedBuffer::new(buf)?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct MappedBuffer<T: Copy> {
+    size: usize,
+    ptr: *mut c_void,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Copy> MappedBuffer<T> {
+    pub fn new<B: AsRef<[T]>>(buf: B) -> Result<Self, Error> {
+        Self::map_copy(buf.as_ref(), tempfile)
+    }
+
+    /// Creates a mapping of the buffer backed by a tempfile in `dir`,
+    /// instead of the system temp directory used by `new`.
+    pub fn new_in<B: AsRef<[T]>, P: AsRef<Path>>(buf: B, dir: P) -> Result<Self, Error> {
+        Self::map_copy(buf.as_ref(), || tempfile_in(dir))
+    }
+
+    /// Maps a copy of `buf` to the file created by `backing`
+    fn map_copy(buf: &[T], backing: impl FnOnce() -> Result<File, Error>) -> Result<Self, Error> {
+        let size = size_of_val(buf);
+
+        if size == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Zero size buffer"));
+        }
+
+        if align_of::<T>() > page_size() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Element alignment exceeds the page size",
+            ));
+        }
+
+        let file = backing()?;
+
+        file.set_len(size as u64)?;
+
+        // SAFETY: FFI. Valid file handle for temp-phys file, zero maximum size
+        // maps the whole file + processed `CreateFileMappingW` result
+        let mapping = unsafe {
+            CreateFileMappingW(
+                file.as_raw_handle(),
+                ptr::null(),
+                PAGE_READWRITE,
+                0,
+                0,
+                ptr::null(),
+            )
+        };
+
+        if mapping.is_null() {
+            return Err(Error::last_os_error());
+        }
+
+        // SAFETY: FFI. Valid mapping handle + processed `MapViewOfFile` result
+        let view = unsafe {
+            MapViewOfFile(mapping, FILE_MAP_ALL_ACCESS, 0, 0, size)
+        };
+        let err = Error::last_os_error();
+
+        // SAFETY: FFI. Valid mapping handle, the view keeps the mapping alive
+        unsafe {
+            CloseHandle(mapping);
+        }
+
+        let ptr = view.Value;
+
+        if ptr.is_null() {
+            return Err(err);
+        }
+
+        // The view is owned by `mapped_buf` from here on, so it is unmapped on panic
+        let mapped_buf = Self {
+            ptr,
+            size,
+            _phantom: PhantomData,
+        };
+        // Views are aligned to the allocation granularity, which is a multiple of the page size
+        debug_assert!(ptr.cast::<T>().is_aligned());
+
+        // SAFETY: The pointer `ptr` is valid for writing `buf.len()` elements
+        // and page aligned, so properly aligned for type `T`.
+        unsafe {
+            ptr::copy_nonoverlapping(buf.as_ptr(), ptr.cast(), buf.len());
+        }
+
+        Ok(mapped_buf)
+    }
+
+    /// Returns the number of `T` elements in the mapped buffer.
+    pub fn len(&self) -> usize {
+        self.size / size_of::<T>()
+    }
+
+    /// Returns the number of bytes mapped for the buffer, `len() * size_of::<T>()`.
+    pub fn capacity_bytes(&self) -> usize {
+        self.size
+    }
+
+    /// Returns `true` if the mapped buffer holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the address the view of the mapping starts at,
+    /// which is aligned to the allocation granularity.
+    pub fn addr(&self) -> usize {
+        self.ptr as usize
+    }
+
+    /// If `receive` is successful, It returns a slice that represents the mapped buffer.
+    pub fn receive(&self) -> &[T] {
+        // SAFETY: The pointer `self.ptr` is valid for
+        // reading `self.size` bytes and that these bytes are properly aligned for type `T`.
+        unsafe {
+            std::slice::from_raw_parts(self.ptr.cast(), self.len())
+        }
+    }
+
+    /// If `receive_mut` is successful, It returns a mutable slice that represents the mapped buffer.
+    /// Writes go straight to the shared mapping.
+    pub fn receive_mut(&mut self) -> &mut [T] {
+        // SAFETY: The pointer `self.ptr` is valid for reading and writing
+        // `self.size` bytes, properly aligned for type `T` and uniquely borrowed via `&mut self`.
+        unsafe {
+            std::slice::from_raw_parts_mut(self.ptr.cast(), self.len())
+        }
+    }
+}
+
+// SAFETY: `MappedBuffer` exclusively owns its mapping, `ptr` is never aliased by another
+// `MappedBuffer` and the mapping stays valid until drop, so moving it between threads
+// is like moving a `Box<[T]>`
+unsafe impl<T: Copy + Send> Send for MappedBuffer<T> {}
+
+// SAFETY: Shared access only hands out `&[T]`, mutation requires `&mut self`,
+// so sharing `&MappedBuffer` between threads is like sharing `&[T]`
+unsafe impl<T: Copy + Sync> Sync for MappedBuffer<T> {}
+
+impl<T: Copy> fmt::Pointer for MappedBuffer<T> {
+    /// Prints the address the view starts at, same as `addr`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.ptr, f)
+    }
+}
+
+impl<T: Copy> Deref for MappedBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.receive()
+    }
+}
+
+impl<T: Copy> DerefMut for MappedBuffer<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.receive_mut()
+    }
+}
+
+impl<T: Copy> AsRef<[T]> for MappedBuffer<T> {
+    fn as_ref(&self) -> &[T] {
+        self.receive()
+    }
+}
+
+impl<T: Copy> AsMut<[T]> for MappedBuffer<T> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.receive_mut()
+    }
+}
+
+impl<T: Copy> Drop for MappedBuffer<T> {
+    fn drop(&mut self) {
+        // SAFETY: FFI. Valid view address of the mapping
+        unsafe {
+            UnmapViewOfFile(MEMORY_MAPPED_VIEW_ADDRESS { Value: self.ptr });
+        }
+    }
+}
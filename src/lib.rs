@@ -1,22 +1,67 @@
 //! A crate that helps to optimize large buffers in near-OOM state or with small amount of RAM
+//!
+//! Without the default `std` feature the crate is `no_std` (with `alloc`),
+//! and only provides the `Locker`, `Adviser` and `Guard` wrappers.
+//!
+//! With the `tracing` feature every `mmap`, `munmap`, `mlock` and `madvise` call (and their
+//! variants) is reported as a `tracing` event with its arguments, at the `WARN` level if it failed.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub use file::*;
+#[cfg(unix)]
+pub use errno::Errno;
+#[cfg(any(unix, windows))]
+pub use page::page_size;
 pub use wrappers::*;
 
+/// Include `trace_syscall`
+#[cfg(unix)]
+#[macro_use]
+mod trace;
+
+#[cfg(feature = "std")]
 mod file {
-    /// Include `MappedBuffer`
+    /// Include `MappedBufferBuilder`
+    #[cfg(unix)]
+    pub mod builder;
+    /// Include `MappedBuffer`, `ByteBuffer`, `ReadOnlyMappedBuffer`, `IntoIter`,
+    /// `SyncMode`, `MappingStats`, `LockPolicy`, `MapError`, `AdvisedChunks`,
+    /// `Seals`
     #[cfg(unix)]
     pub mod mapper;
+    /// Include `MappedBuffer`
+    #[cfg(windows)]
+    #[path = "windows/mapper.rs"]
+    pub mod mapper;
 }
 
 mod wrappers {
-    /// Include `Adviser`, `Advise`, `AdviseError`
+    /// Include `Adviser`, `AdviseGuard`, `Advise`, `LinuxAdvise`, `AdviseError`
     #[cfg(unix)]
     pub mod advisor;
-    /// Include `Locker`, `LockError`
+    /// Include `Guard`, `GuardError`
+    #[cfg(unix)]
+    pub mod guard;
+    /// Include `Locker`, `LockError`, `LockFlags`, `LockAllFlags`, `lock_all`, `unlock_all`,
+    /// `lockable_bytes`
     #[cfg(unix)]
     pub mod locker;
+    /// Include `Locker`, `LockError`
+    #[cfg(windows)]
+    #[path = "windows/locker.rs"]
+    pub mod locker;
 }
 
-#[cfg(test)]
+/// Include `Errno`
+#[cfg(unix)]
+mod errno;
+/// Include `page_size`
+#[cfg(any(unix, windows))]
+mod page;
+
+#[cfg(all(test, feature = "std", unix))]
 mod test;
\ No newline at end of file
@@ -0,0 +1,51 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Cached page size, `0` until it is first queried
+static PAGE_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the system page size in bytes, which is queried once and cached.
+/// # Examples
+///
+/// ```
+/// use memguar::page_size;
+///
+/// let buf = vec![0u8; 16_000];
+/// let pages = buf.len().div_ceil(page_size());
+/// ```
+#[cfg(unix)]
+pub fn page_size() -> usize {
+    cached(|| {
+        // SAFETY: FFI. `_SC_PAGESIZE` is always a valid name
+        unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+    })
+}
+
+/// Returns the system page size in bytes, which is queried once and cached.
+#[cfg(windows)]
+pub fn page_size() -> usize {
+    use windows_sys::Win32::System::SystemInformation::{GetSystemInfo, SYSTEM_INFO};
+
+    cached(|| {
+        // SAFETY: `SYSTEM_INFO` is a plain C struct, for which all-zero bytes are valid
+        let mut info: SYSTEM_INFO = unsafe { core::mem::zeroed() };
+        // SAFETY: FFI. Valid pointer to `info`
+        unsafe {
+            GetSystemInfo(&mut info);
+        }
+
+        info.dwPageSize as usize
+    })
+}
+
+/// Returns the cached page size, querying it first if needed.
+/// Racing threads may both query it, which is harmless since the result is the same.
+fn cached(query: impl FnOnce() -> usize) -> usize {
+    match PAGE_SIZE.load(Ordering::Relaxed) {
+        0 => {
+            let page_size = query();
+            PAGE_SIZE.store(page_size, Ordering::Relaxed);
+            page_size
+        }
+        page_size => page_size,
+    }
+}
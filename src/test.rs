@@ -1,7 +1,12 @@
+use std::sync::Arc;
+
 use crate::advisor::*;
-use crate::advisor::Advise::DontNeed;
+use crate::advisor::Advise::{DontNeed, Normal, Random, Sequential, WillNeed};
+use crate::builder::MappedBufferBuilder;
+use crate::guard::{Guard, GuardError};
 use crate::locker::*;
-use crate::mapper::MappedBuffer;
+use crate::mapper::{ByteBuffer, LockPolicy, MapError, MappedBuffer, SyncMode};
+use crate::{page_size, Errno};
 
 #[test]
 pub fn locker() -> Result<(), LockError> {
@@ -12,6 +17,510 @@ pub fn locker() -> Result<(), LockError> {
         .lock()
 }
 
+#[test]
+pub fn locker_try_new() -> Result<(), LockError> {
+    let buf = vec![420; 16_000];
+    let locked_buf = Locker::try_new(buf).map_err(|(_buf, err)| err)?;
+
+    assert_eq!(locked_buf.get().len(), 16_000);
+
+    Ok(())
+}
+
+#[test]
+pub fn locker_one_shot() -> Result<(), LockError> {
+    let mut buf = vec![420; 16_000];
+    lock(&buf)?;
+    unlock(&buf[..8_000])?;
+
+    buf[0] = 69;
+    unlock(&buf)
+}
+
+#[test]
+pub fn locker_readonly() -> Result<(), LockError> {
+    let buf: Arc<[i32]> = Arc::from(vec![420; 16_000]);
+    let mut locked_buf = Locker::new_readonly(Arc::clone(&buf));
+    locked_buf.lock()?;
+
+    let locked_copy = locked_buf.try_clone()?;
+    assert_eq!(locked_copy.get(), &buf[..]);
+
+    locked_buf.unlock()
+}
+
+#[test]
+pub fn locker_try_clone() -> Result<(), LockError> {
+    let buf = vec![420; 16_000];
+    let mut locked_buf = Locker::try_new(buf).map_err(|(_buf, err)| err)?;
+    let mut locked_copy = locked_buf.try_clone()?;
+
+    assert_ne!(locked_buf.get().as_ptr(), locked_copy.get().as_ptr());
+    assert!(locked_buf.residency()?.into_iter().all(|page| page));
+    assert!(locked_copy.residency()?.into_iter().all(|page| page));
+
+    locked_buf.unlock()?;
+    locked_copy.unlock()?;
+
+    Ok(())
+}
+
+#[test]
+pub fn locker_close() -> Result<(), LockError> {
+    let buf = vec![420; 16_000];
+    let locked_buf = Locker::try_new(buf).map_err(|(_buf, err)| err)?;
+    let buf = locked_buf.close().map_err(|(_buf, err)| err)?;
+
+    assert_eq!(buf.len(), 16_000);
+
+    Ok(())
+}
+
+/// Locked kB of the mappings within `range`, from `/proc/self/smaps`,
+/// since unlocking a part of a mapping splits it. These are the per-mapping counts
+/// behind `VmLck` in `/proc/self/status`, which covers the whole process
+/// and so would include the locks of other tests
+#[cfg(target_os = "linux")]
+fn locked_kb(range: std::ops::Range<usize>) -> usize {
+    let smaps = std::fs::read_to_string("/proc/self/smaps").unwrap();
+    let mut within = false;
+
+    smaps
+        .lines()
+        .filter_map(|line| {
+            let start = line.split_once('-').map(|(start, _)| usize::from_str_radix(start, 16));
+
+            if let Some(Ok(start)) = start {
+                within = range.contains(&start);
+            }
+
+            let locked = line.strip_prefix("Locked:").filter(|_| within)?;
+            locked.trim().strip_suffix("kB")?.trim().parse::<usize>().ok()
+        })
+        .sum()
+}
+
+#[test]
+pub fn locker_never_locked() {
+    // Dropping an unlocked `Locker` doesn't unlock, so even a zero size buffer doesn't panic
+    let buf: Vec<u8> = Vec::new();
+    let locked_buf = Locker::new(buf);
+
+    drop(locked_buf);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn locker_locked_dropped() -> Result<(), Box<dyn std::error::Error>> {
+    let page_size = page_size();
+    let mut mapped_buf = ByteBuffer::new_bytes(2 * page_size)?;
+    let addr = mapped_buf.as_ptr() as usize;
+    let range = addr..addr + 2 * page_size;
+    let mut locked_buf = Locker::new(&mut mapped_buf[..]);
+
+    locked_buf.lock()?;
+    locked_buf.lock()?;
+    assert_eq!(locked_kb(range.clone()), 2 * page_size / 1024);
+
+    drop(locked_buf);
+    assert_eq!(locked_kb(range), 0);
+
+    Ok(())
+}
+
+#[test]
+pub fn locker_double_unlock() -> Result<(), LockError> {
+    let buf = vec![420; 16_000];
+    let mut locked_buf = Locker::new(buf);
+    locked_buf.unlock()?;
+
+    locked_buf.lock()?;
+    locked_buf.unlock()?;
+    locked_buf.unlock()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn locker_relock() -> Result<(), Box<dyn std::error::Error>> {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Exposes one of its buffers, like a `Vec` that may be reallocated elsewhere
+    struct Moving(Vec<ByteBuffer>, Rc<Cell<usize>>);
+
+    impl AsMut<[u8]> for Moving {
+        fn as_mut(&mut self) -> &mut [u8] {
+            &mut self.0[self.1.get()]
+        }
+    }
+
+    let page_size = page_size();
+    let bufs = vec![ByteBuffer::new_bytes(2 * page_size)?, ByteBuffer::new_bytes(2 * page_size)?];
+    let ranges: Vec<_> = bufs
+        .iter()
+        .map(|buf| buf.as_ptr() as usize..buf.as_ptr() as usize + 2 * page_size)
+        .collect();
+    let current = Rc::new(Cell::new(0));
+    let mut locked_buf = Locker::new(Moving(bufs, current.clone()));
+
+    locked_buf.relock()?;
+    assert_eq!(locked_kb(ranges[0].clone()), 2 * page_size / 1024);
+
+    locked_buf.relock()?;
+    assert_eq!(locked_kb(ranges[0].clone()), 2 * page_size / 1024);
+
+    current.set(1);
+    locked_buf.relock()?;
+    assert_eq!(locked_kb(ranges[0].clone()), 0);
+    assert_eq!(locked_kb(ranges[1].clone()), 2 * page_size / 1024);
+
+    // `unlock` releases the pages locked last, not the ones the buffer has moved to
+    current.set(0);
+    locked_buf.unlock()?;
+    assert_eq!(locked_kb(ranges[1].clone()), 0);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn locker_counted() -> Result<(), Box<dyn std::error::Error>> {
+    let page_size = page_size();
+    let mut mapped_buf = ByteBuffer::new_bytes(2 * page_size)?;
+    let addr = mapped_buf.as_ptr() as usize;
+    let range = addr..addr + 2 * page_size;
+    let (left, right) = mapped_buf.split_at_mut(page_size + 100);
+    let mut locked_left = Locker::new(left).counted();
+    let mut locked_right = Locker::new(right).counted();
+
+    locked_left.lock()?;
+    locked_right.lock()?;
+    assert_eq!(locked_kb(range.clone()), 2 * page_size / 1024);
+
+    locked_left.unlock()?;
+    assert_eq!(locked_kb(range.clone()), page_size / 1024);
+
+    locked_right.unlock()?;
+    assert_eq!(locked_kb(range), 0);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn locker_try_clone_counted() -> Result<(), Box<dyn std::error::Error>> {
+    let page_size = page_size();
+    let mapped_buf = ByteBuffer::new_bytes(2 * page_size)?;
+    let addr = mapped_buf.as_ptr() as usize;
+    let range = addr..addr + 2 * page_size;
+    // Cloning a borrowed slice shares its pages with the original
+    let mut locked_buf = Locker::new_readonly(&mapped_buf[..]).counted();
+
+    locked_buf.lock()?;
+    drop(locked_buf.try_clone()?);
+    assert_eq!(locked_kb(range.clone()), 2 * page_size / 1024);
+
+    locked_buf.unlock()?;
+    assert_eq!(locked_kb(range), 0);
+
+    Ok(())
+}
+
+#[test]
+pub fn locker_into_inner() -> Result<(), LockError> {
+    let buf = vec![420; 16_000];
+    let mut locked_buf = Locker::new(buf);
+    locked_buf.lock()?;
+    let buf = locked_buf.into_inner();
+
+    assert_eq!(buf.len(), 16_000);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn locker_on_fault() -> Result<(), LockError> {
+    let buf = vec![420u64; 16_000];
+    let mut locked_buf = Locker::new(buf);
+
+    locked_buf.lock_on_fault()?;
+
+    locked_buf.lock_with(LockFlags::empty())
+}
+
+#[test]
+pub fn locker_range() -> Result<(), LockError> {
+    let buf = vec![420u64; 16_000];
+    let mut locked_buf = Locker::new(buf);
+    locked_buf.lock_range(1_000, 2_000)?;
+    locked_buf.unlock_range(1_000, 2_000)?;
+
+    assert!(matches!(locked_buf.lock_range(15_000, 2_000), Err(LockError::EINVAL)));
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn locker_range_then_whole() -> Result<(), Box<dyn std::error::Error>> {
+    let page_size = page_size();
+    let mut mapped_buf = ByteBuffer::new_bytes(4 * page_size)?;
+    let addr = mapped_buf.as_ptr() as usize;
+    let range = addr..addr + 4 * page_size;
+    let mut locked_buf = Locker::new(&mut mapped_buf[..]);
+
+    locked_buf.lock_range(0, page_size)?;
+    assert_eq!(locked_kb(range.clone()), page_size / 1024);
+    locked_buf.lock()?;
+    assert_eq!(locked_kb(range.clone()), 4 * page_size / 1024);
+
+    locked_buf.unlock_range(0, 4 * page_size)?;
+    assert_eq!(locked_kb(range.clone()), 0);
+    locked_buf.lock()?;
+    assert_eq!(locked_kb(range.clone()), 4 * page_size / 1024);
+
+    locked_buf.unlock()?;
+    assert_eq!(locked_kb(range), 0);
+
+    Ok(())
+}
+
+#[test]
+pub fn locker_residency() -> Result<(), LockError> {
+    let buf = vec![420u64; 16_000];
+    let mut locked_buf = Locker::new(buf);
+    locked_buf.lock()?;
+    let residency = locked_buf.residency()?;
+
+    assert!(!residency.is_empty());
+    assert!(residency.iter().all(|&resident| resident));
+
+    Ok(())
+}
+
+#[test]
+pub fn locker_oversized() -> Result<(), LockError> {
+    const LEN: u64 = 64 * 1024 * 1024;
+    let buf = vec![0u8; LEN as usize];
+    let mut locked_buf = Locker::new(buf);
+
+    // Whether it fits depends on `RLIMIT_MEMLOCK` and the privileges of the process
+    match locked_buf.lock() {
+        Ok(()) => {
+            assert!(locked_buf.residency()?.iter().all(|&resident| resident));
+            locked_buf.unlock()
+        }
+        Err(err) => {
+            // `ENOMEM` if other locked memory of the process already exhausts the limit
+            let limit = lockable_bytes()?;
+            assert!(
+                matches!(
+                    err,
+                    LockError::LimitExceeded { requested: LEN, limit: l } if l == limit
+                ) || matches!(err, LockError::ENOMEM | LockError::EPERM | LockError::EAGAIN),
+                "{err:?}"
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Drops `CAP_IPC_LOCK` from the calling thread, so `RLIMIT_MEMLOCK` applies to it
+/// even if the tests run as root. Capabilities are per thread, other tests keep theirs.
+/// Returns `false` if the capabilities can't be changed.
+#[cfg(target_os = "linux")]
+fn drop_ipc_lock() -> bool {
+    use libc::c_int;
+
+    const CAP_IPC_LOCK: u32 = 14;
+
+    #[repr(C)]
+    struct Header {
+        version: u32,
+        pid: c_int,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct Data {
+        effective: u32,
+        permitted: u32,
+        inheritable: u32,
+    }
+
+    // `_LINUX_CAPABILITY_VERSION_3`, with two `Data` for the 64 capability bits
+    let mut header = Header { version: 0x2008_0522, pid: 0 };
+    let mut data = [Data::default(); 2];
+
+    // SAFETY: FFI. Valid header and two data structs + processed `capget` result
+    let result = unsafe {
+        libc::syscall(libc::SYS_capget, &mut header, data.as_mut_ptr())
+    };
+
+    if result != 0 {
+        return false;
+    }
+
+    data[0].effective &= !(1 << CAP_IPC_LOCK);
+
+    // SAFETY: FFI. Valid header and two data structs + processed `capset` result
+    let result = unsafe {
+        libc::syscall(libc::SYS_capset, &mut header, data.as_ptr())
+    };
+
+    result == 0
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn locker_limit() -> Result<(), LockError> {
+    let limit = lockable_bytes()?;
+    assert!(limit > 0);
+
+    if limit == u64::MAX {
+        return Ok(());
+    }
+
+    let requested = limit + page_size() as u64;
+
+    // Without `CAP_IPC_LOCK` the limit applies, even to the already locked bytes of other tests
+    std::thread::spawn(move || {
+        if !drop_ipc_lock() {
+            return;
+        }
+
+        let buf = vec![0u8; requested as usize];
+        let mut locked_buf = Locker::new(buf);
+        let err = locked_buf.lock();
+        assert!(
+            matches!(err, Err(LockError::LimitExceeded { requested: r, limit: l })
+                if r == requested && l == limit),
+            "{err:?}"
+        );
+    })
+    .join()
+    .unwrap();
+
+    Ok(())
+}
+
+#[test]
+pub fn lock_error_display() {
+    let errors = [
+        LockError::EPERM,
+        LockError::EINTR,
+        LockError::EIO,
+        LockError::EAGAIN,
+        LockError::ENOMEM,
+        LockError::EFAULT,
+        LockError::EBUSY,
+        LockError::EINVAL,
+        LockError::ENOSYS,
+        LockError::LimitExceeded {
+            requested: 1 << 30,
+            limit: 1 << 16,
+        },
+        LockError::EUNIM(-1),
+    ];
+
+    for err in errors {
+        assert!(!err.to_string().is_empty());
+    }
+}
+
+#[test]
+pub fn raw_errno() {
+    let errors = [
+        libc::EPERM,
+        libc::EINTR,
+        libc::EIO,
+        libc::EAGAIN,
+        libc::ENOMEM,
+        libc::EFAULT,
+        libc::EBUSY,
+        libc::EINVAL,
+        libc::ENOSYS,
+        420,
+    ];
+
+    for err in errors {
+        assert_eq!(LockError::from(err).raw_errno(), err);
+    }
+
+    for err in [libc::ENOMEM, libc::EFAULT, libc::EINVAL, libc::ENOSYS, 420] {
+        assert_eq!(AdviseError::from(err).raw_errno(), err);
+    }
+}
+
+#[test]
+pub fn errno() {
+    assert_eq!(LockError::ENOMEM.errno(), Errno::from(libc::ENOMEM));
+    assert_eq!(LockError::ENOMEM.to_string(), AdviseError::ENOMEM.to_string());
+    assert_eq!(AdviseError::EUNIM(libc::EACCES).errno().name(), Some("EACCES"));
+    assert_eq!(Errno::from(420).name(), None);
+    assert!(Errno::from(420).to_string().contains("420"));
+}
+
+#[test]
+pub fn is_transient() {
+    let errors = [
+        (LockError::EPERM, false),
+        (LockError::EINTR, true),
+        (LockError::EIO, false),
+        (LockError::EAGAIN, true),
+        (LockError::ENOMEM, false),
+        (LockError::EFAULT, false),
+        (LockError::EBUSY, false),
+        (LockError::EINVAL, false),
+        (LockError::ENOSYS, false),
+        (
+            LockError::LimitExceeded {
+                requested: 1 << 30,
+                limit: 1 << 16,
+            },
+            false,
+        ),
+        (LockError::EUNIM(420), false),
+    ];
+
+    for (err, transient) in errors {
+        assert_eq!(err.is_transient(), transient, "{err:?}");
+    }
+
+    let errors = [
+        (AdviseError::EFAULT, false),
+        (AdviseError::EINVAL, false),
+        (AdviseError::ENOMEM, false),
+        (AdviseError::ENOSYS, false),
+        (AdviseError::EUNIM(libc::EAGAIN), true),
+        (AdviseError::EUNIM(libc::EINTR), true),
+        (AdviseError::EUNIM(libc::EACCES), false),
+        (AdviseError::EUNIM(420), false),
+    ];
+
+    for (err, transient) in errors {
+        assert_eq!(err.is_transient(), transient, "{err:?}");
+    }
+}
+
+#[test]
+pub fn try_from_errno() {
+    assert!(LockError::try_from_errno(0).is_none());
+    assert!(matches!(LockError::try_from_errno(libc::ENOMEM), Some(LockError::ENOMEM)));
+    assert!(AdviseError::try_from_errno(0).is_none());
+    assert!(matches!(AdviseError::try_from_errno(libc::EINVAL), Some(AdviseError::EINVAL)));
+}
+
+#[test]
+pub fn libc_errno() {
+    assert!(matches!(LockError::from(libc::ENOMEM), LockError::ENOMEM));
+    assert!(matches!(LockError::from(libc::EAGAIN), LockError::EAGAIN));
+    assert!(matches!(LockError::from(libc::ENOSYS), LockError::ENOSYS));
+    assert!(matches!(AdviseError::from(libc::ENOMEM), AdviseError::ENOMEM));
+    assert!(matches!(AdviseError::from(libc::ENOSYS), AdviseError::ENOSYS));
+}
+
 #[test]
 pub fn advisor() -> Result<(), AdviseError> {
     let buf = [420; 16_000];
@@ -22,10 +531,1318 @@ pub fn advisor() -> Result<(), AdviseError> {
 }
 
 #[test]
-pub fn mapper() -> Result<(), std::io::Error> {
-    let buf = [420; 16_000];
-    let mapped_buf = MappedBuffer::new(buf)?;
-    let _buf = mapped_buf.receive();
-    
+pub fn advisor_variants() -> Result<(), AdviseError> {
+    let buf = vec![420; 16_000];
+    let mut advised_buf = Adviser::new(buf);
+
+    for advise in [Normal, Random, Sequential, WillNeed, DontNeed] {
+        advised_buf.syscall_advise(advise)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_readonly() -> Result<(), AdviseError> {
+    let buf: Arc<[i32]> = Arc::from(vec![420; 16_000]);
+    let mut advised_buf = Adviser::new_readonly(Arc::clone(&buf));
+
+    advised_buf.syscall_advise(WillNeed)?;
+    advised_buf.advise_range(4_000, 8_000, Sequential)?;
+    assert_eq!(advised_buf.last_advise(), Some(WillNeed));
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_reuse() -> Result<(), AdviseError> {
+    let buf = vec![420; 16_000];
+    let mut advised_buf = Adviser::new(buf);
+    let advise = WillNeed;
+
+    advised_buf.syscall_advise(advise)?;
+    advised_buf.advise_range(0, 8_000, advise)?;
+    assert_eq!(advise, WillNeed);
+    assert_eq!(format!("{advise:?}"), "WillNeed");
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_one_shot() -> Result<(), AdviseError> {
+    let mut buf = vec![420; 16_000];
+    advise(&buf, WillNeed)?;
+    advise(&buf[4_000..8_000], DontNeed)?;
+    advise(&buf[..0], WillNeed)?;
+    advise::<u8>(&[], DontNeed)?;
+
+    buf[0] = 69;
+    assert_eq!(buf[0], 69);
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_range() -> Result<(), AdviseError> {
+    let buf = vec![420; 16_000];
+    let mut advised_buf = Adviser::new(buf);
+
+    advised_buf.advise_range(4_000, 8_000, WillNeed)?;
+    advised_buf.advise_range(0, 16_000, Normal)?;
+    advised_buf.advise_range(16_000, 0, DontNeed)?;
+
+    assert!(matches!(
+        advised_buf.advise_range(8_000, 8_001, Random),
+        Err(AdviseError::EINVAL)
+    ));
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_many() -> Result<(), AdviseError> {
+    let buf = vec![420; 64_000];
+    let mut advised_buf = Adviser::new(buf);
+
+    advised_buf.advise_many(&[(0, 100), (50, 200), (250, 10), (32_000, 1_000)], WillNeed)?;
+    advised_buf.advise_many(&[(60_000, 4_000), (0, 0), (0, 64_000)], Normal)?;
+    advised_buf.advise_many(&[], DontNeed)?;
+
+    assert!(matches!(
+        advised_buf.advise_many(&[(0, 100), (63_999, 2)], Random),
+        Err(AdviseError::EINVAL)
+    ));
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn advisor_reclaim() -> Result<(), Box<dyn std::error::Error>> {
+    let page_size = page_size();
+    let mut mapped_buf = ByteBuffer::new_bytes(4 * page_size)?;
+    mapped_buf.fill(42);
+    let mut advised_buf = Adviser::new(&mut mapped_buf[..]);
+
+    for advise in [LinuxAdvise::Cold, LinuxAdvise::PageOut] {
+        match advised_buf.advise_linux(advise) {
+            // Kernels before 5.4 don't know the reclaim hints
+            Ok(()) | Err(AdviseError::ENOSYS) => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    drop(advised_buf);
+    // Reclaimed pages are read back from the backing file
+    assert!(mapped_buf.iter().all(|&byte| byte == 42));
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_all() -> Result<(), (usize, AdviseError)> {
+    let buf = vec![420; 16_000];
+    let mut advised_buf = Adviser::new(buf);
+
+    advised_buf.advise_all(&[
+        (0..8_000, Sequential),
+        (8_000..16_000, WillNeed),
+        (0..16_000, DontNeed),
+    ])?;
+    advised_buf.advise_all(&[])?;
+
+    assert!(matches!(
+        advised_buf.advise_all(&[(0..8_000, Normal), (8_000..16_001, Random), (0..1, Normal)]),
+        Err((1, AdviseError::EINVAL))
+    ));
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = advised_buf.advise_all(&[(8_000..4_000, Normal)]);
+    assert!(matches!(reversed, Err((0, AdviseError::EINVAL))));
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_scoped() -> Result<(), AdviseError> {
+    let buf = vec![420; 16_000];
+    let mut advised_buf = Adviser::new(buf);
+    let guard = advised_buf.advise_scoped(Sequential)?;
+    drop(guard);
+
+    let _guard = advised_buf.advise_scoped_with(Random, WillNeed)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_last_advise() -> Result<(), AdviseError> {
+    let buf = vec![420; 16_000];
+    let mut advised_buf = Adviser::new(buf);
+    assert_eq!(advised_buf.last_advise(), None);
+
+    advised_buf.syscall_advise(WillNeed)?;
+    assert_eq!(advised_buf.last_advise(), Some(WillNeed));
+
+    drop(advised_buf.advise_scoped_with(Sequential, Random)?);
+    assert_eq!(advised_buf.last_advise(), Some(Random));
+
+    Ok(())
+}
+
+#[test]
+pub fn advisor_drop_advise() {
+    let buf = vec![420; 16_000];
+    let advised_buf = Adviser::new(buf).with_drop_advise(WillNeed);
+
+    drop(advised_buf);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn advisor_linux() -> Result<(), AdviseError> {
+    let buf = vec![420u64; 16_000];
+    let mut advised_buf = Adviser::new(buf);
+    advised_buf.advise_linux(LinuxAdvise::DontDump)?;
+    advised_buf.advise_linux(LinuxAdvise::DoDump)?;
+    assert!(matches!(advised_buf.advise_linux(LinuxAdvise::Free), Err(AdviseError::EINVAL)));
+    assert!(matches!(advised_buf.advise_linux(LinuxAdvise::Remove), Err(AdviseError::EINVAL)));
+    // SAFETY: All zero bytes are a valid `u64`
+    unsafe { advised_buf.advise_linux_destructive(LinuxAdvise::Free)? };
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn advisor_huge_pages() -> Result<(), Box<dyn std::error::Error>> {
+    // SAFETY: All zero bytes are a valid `u64`
+    let mut advised_buf = Adviser::new(unsafe { MappedBuffer::<u64>::anonymous(1_000_000)? });
+
+    match advised_buf.set_huge_pages(true) {
+        // Kernels without transparent huge pages reject the hint
+        Err(AdviseError::EINVAL) => return Ok(()),
+        result => result?,
+    }
+    advised_buf.set_huge_pages(false)?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn advisor_fork_inherit() -> Result<(), Box<dyn std::error::Error>> {
+    // SAFETY: All zero bytes are a valid `u64`
+    let mut advised_buf = Adviser::new(unsafe { MappedBuffer::<u64>::anonymous(16_000)? });
+    advised_buf.set_fork_inherit(false)?;
+    advised_buf.set_fork_inherit(true)?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+pub fn advise_error_display() {
+    let errors = [
+        AdviseError::EFAULT,
+        AdviseError::EINVAL,
+        AdviseError::ENOMEM,
+        AdviseError::ENOSYS,
+    ];
+
+    for err in errors {
+        assert!(!err.to_string().is_empty());
+    }
+    assert!(AdviseError::EUNIM(420).to_string().contains("420"));
+}
+
+#[test]
+pub fn mapper() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    let _buf = mapped_buf.receive();
+    
+    Ok(())
+}
+
+#[test]
+pub fn mapper_len() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    assert_eq!(mapped_buf.len(), 16_000);
+    assert!(!mapped_buf.is_empty());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_as_bytes() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    // SAFETY: `i32` has no padding bytes
+    assert_eq!(unsafe { mapped_buf.as_bytes() }.len(), size_of_val(&buf));
+    assert_eq!(mapped_buf.as_ptr(), mapped_buf.receive().as_ptr());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_deref_mut() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+    mapped_buf[3] = 69;
+    mapped_buf.sort();
+
+    assert_eq!(mapped_buf.receive()[0], 69);
+    assert_eq!(mapped_buf.receive()[1], 420);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_deref_mut_persisted() -> Result<(), std::io::Error> {
+    let path = std::env::temp_dir().join(format!("memguar-deref-mut-{}", std::process::id()));
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBufferBuilder::new()
+        .path(Some(path.clone()))
+        .build_from(buf)?;
+    mapped_buf[3] = 69;
+    mapped_buf.sort();
+    drop(mapped_buf);
+
+    // A second mapping of the same file sees the writes
+    // SAFETY: Any bytes are a valid `i32`
+    let remapped_buf = unsafe { MappedBuffer::<i32>::from_path(&path, 16_000) };
+    std::fs::remove_file(&path)?;
+    let remapped_buf = remapped_buf?;
+
+    assert_eq!(remapped_buf.receive()[0], 69);
+    assert_eq!(remapped_buf.receive()[1..], [420; 15_999]);
+
+    Ok(())
+}
+
+#[test]
+pub fn builder_path_longer_file() -> Result<(), std::io::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("buf.bin");
+    std::fs::write(&path, [42u8; 16_000])?;
+
+    let mapped_buf = MappedBufferBuilder::new()
+        .path(Some(path.clone()))
+        .build_from([69u8; 8_000])?;
+    drop(mapped_buf);
+
+    let contents = std::fs::read(&path)?;
+    assert_eq!(contents.len(), 16_000);
+    assert_eq!(contents[..8_000], [69; 8_000]);
+    assert_eq!(contents[8_000..], [42; 8_000]);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_anonymous() -> Result<(), std::io::Error> {
+    // SAFETY: All zero bytes are a valid `u64`
+    let mut mapped_buf = unsafe { MappedBuffer::<u64>::anonymous(16_000)? };
+    assert!(mapped_buf.iter().all(|&x| x == 0));
+    mapped_buf[15_999] = 420;
+
+    assert_eq!(mapped_buf.len(), 16_000);
+    assert_eq!(mapped_buf[15_999], 420);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_from_path() -> Result<(), std::io::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("buf.bin");
+    // SAFETY: Any bytes are a valid `u64`
+    let mut mapped_buf = unsafe { MappedBuffer::<u64>::from_path(&path, 16_000)? };
+    mapped_buf[42] = 420;
+    mapped_buf.flush()?;
+    drop(mapped_buf);
+
+    // SAFETY: Any bytes are a valid `u64`
+    let mapped_buf = unsafe { MappedBuffer::<u64>::from_path(&path, 16_000)? };
+    assert_eq!(mapped_buf[42], 420);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_advise() -> Result<(), AdviseError> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf).unwrap();
+    mapped_buf.advise(WillNeed)?;
+
+    mapped_buf.advise(DontNeed)
+}
+
+#[test]
+pub fn mapper_lock() -> Result<(), LockError> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf).unwrap();
+    mapped_buf.lock()?;
+
+    mapped_buf.unlock()
+}
+
+#[test]
+pub fn mapper_private() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new_private(buf)?;
+    mapped_buf[0] = 69;
+
+    assert_eq!(mapped_buf[0], 69);
+    assert_eq!(mapped_buf[1], 420);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_readonly() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new_readonly(buf)?;
+
+    assert_eq!(mapped_buf.len(), 16_000);
+    assert!(mapped_buf.receive().iter().all(|&x| x == 420));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_populated() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new_populated(buf)?;
+
+    assert!(mapped_buf.receive().iter().all(|&x| x == 420));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_send_sync() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = std::sync::Arc::new(MappedBuffer::new(buf)?);
+    let shared_buf = mapped_buf.clone();
+    let sum = std::thread::spawn(move || shared_buf.iter().sum::<i32>())
+        .join()
+        .unwrap();
+
+    assert_eq!(sum, 420 * 16_000);
+
+    Ok(())
+}
+
+#[test]
+pub fn builder() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBufferBuilder::new()
+        .shared(false)
+        .writable(false)
+        .populate(true)
+        .build_from(buf)?;
+    assert_eq!(mapped_buf.receive(), &buf);
+
+    let builder = MappedBufferBuilder::new().populate(true);
+    // SAFETY: All zero bytes are a valid `u64`
+    let mut mapped_buf = unsafe { builder.build_anon::<u64>(16_000)? };
+    mapped_buf[0] = 420;
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn mapper_huge() {
+    let buf = [420; 16_000];
+    let err = MappedBuffer::new_huge(buf).err().unwrap();
+
+    assert!(matches!(
+        err.kind(),
+        std::io::ErrorKind::InvalidInput | std::io::ErrorKind::Unsupported
+    ));
+}
+
+#[test]
+pub fn mapper_overaligned() {
+    #[derive(Clone, Copy)]
+    #[repr(C, align(65536))]
+    struct Aligned(u8);
+
+    if align_of::<Aligned>() <= page_size() {
+        return;
+    }
+
+    let buf = [Aligned(69)];
+
+    for err in [
+        MappedBuffer::new(buf).err(),
+        // SAFETY: All zero bytes are a valid `Aligned`
+        unsafe { MappedBuffer::<Aligned>::anonymous(1).err() },
+        // SAFETY: All zero bytes are a valid `Aligned`
+        unsafe { MappedBufferBuilder::new().build_anon::<Aligned>(1).err() },
+    ] {
+        let err = err.unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), "Element alignment exceeds the page size");
+    }
+}
+
+#[test]
+pub fn mapper_clone() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    let mut cloned_buf = mapped_buf.clone();
+    cloned_buf[0] = 69;
+
+    assert_eq!(mapped_buf[0], 420);
+    assert_eq!(cloned_buf[1..], buf[1..]);
+    assert_ne!(cloned_buf.as_ptr(), mapped_buf.as_ptr());
+
+    let readonly_buf = MappedBufferBuilder::new()
+        .writable(false)
+        .build_from(buf)?
+        .try_clone()?;
+    assert_eq!(readonly_buf.receive(), &buf);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_iter() -> Result<(), std::io::Error> {
+    let buf: Vec<u64> = (0..16_000).collect();
+    let mapped_buf = MappedBuffer::new(&buf)?;
+
+    assert!(mapped_buf.iter().eq(buf.iter()));
+    assert_eq!((&mapped_buf).into_iter().count(), 16_000);
+
+    let mut iter = mapped_buf.into_iter();
+    assert_eq!(iter.len(), 16_000);
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.next_back(), Some(15_999));
+    assert_eq!(iter.sum::<u64>(), buf[1..15_999].iter().sum());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_try_from() -> Result<(), std::io::Error> {
+    let buf = vec![420; 16_000];
+    let mapped_buf: MappedBuffer<_> = buf.as_slice().try_into()?;
+    assert_eq!(mapped_buf.receive(), buf.as_slice());
+
+    let mapped_buf = MappedBuffer::try_from(buf.clone())?;
+    assert_eq!(mapped_buf.receive(), buf.as_slice());
+
+    let err = MappedBuffer::<u8>::try_from(Vec::new()).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_debug() -> Result<(), std::io::Error> {
+    let buf: Vec<u64> = (0..16_000).collect();
+    let mapped_buf = MappedBuffer::new(buf)?;
+    let debug = format!("{mapped_buf:?}");
+
+    assert!(debug.contains("len: 16000"));
+    assert!(debug.contains("elements: [0, 1, 2, 3, 4, 5, 6, 7, ..]"));
+    assert!(format!("{mapped_buf:#?}").contains("15999"));
+
+    Ok(())
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+pub fn mapper_into_bytes() -> Result<(), std::io::Error> {
+    let buf = [42u8; 16_000];
+    // SAFETY: `u8` has no padding bytes
+    let bytes = unsafe { MappedBuffer::new(buf)?.into_bytes() };
+    let cloned_bytes = bytes.slice(8_000..);
+    drop(bytes);
+
+    assert_eq!(cloned_bytes.len(), 8_000);
+    assert!(cloned_bytes.iter().all(|&byte| byte == 42));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_wrappers() -> Result<(), Box<dyn std::error::Error>> {
+    let buf = [420; 16_000];
+    let mut locked_buf = Locker::new(MappedBuffer::new(buf)?);
+    locked_buf.lock()?;
+    locked_buf.get_mut()[0] = 69;
+    assert_eq!(locked_buf.get()[..2], [69, 420]);
+
+    let mut advised_buf = Adviser::new(MappedBuffer::new(buf)?);
+    advised_buf.syscall_advise(Sequential)?;
+
+    let _guard = Guard::new(MappedBuffer::new(buf)?)?;
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_eq() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    let mut other_buf = MappedBuffer::new_private(buf)?;
+
+    assert_eq!(mapped_buf, other_buf);
+    assert_eq!(mapped_buf, buf[..]);
+
+    other_buf[0] = 69;
+    assert_ne!(mapped_buf, other_buf);
+    assert_ne!(other_buf, buf[..]);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_hash() -> Result<(), std::io::Error> {
+    use std::collections::HashMap;
+    use std::hash::{BuildHasher, RandomState};
+
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    let mut other_buf = MappedBuffer::new_private(buf)?;
+    let state = RandomState::new();
+
+    assert_eq!(state.hash_one(&mapped_buf), state.hash_one(&other_buf));
+    assert_eq!(state.hash_one(&mapped_buf), state.hash_one(&buf[..]));
+
+    other_buf[0] = 69;
+    assert_ne!(state.hash_one(&mapped_buf), state.hash_one(&other_buf));
+
+    let mut buffers = HashMap::new();
+    buffers.insert(mapped_buf, "mapped");
+    assert_eq!(buffers.get(&MappedBuffer::new(buf)?), Some(&"mapped"));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_sync_range() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+    mapped_buf[8_000] = 69;
+
+    mapped_buf.sync_range(8_000, 1, SyncMode::Sync)?;
+    mapped_buf.sync_range(0, 16_000, SyncMode::Async)?;
+    mapped_buf.sync_range(4_000, 4_000, SyncMode::Invalidate)?;
+    mapped_buf.sync_range(16_000, 0, SyncMode::Sync)?;
+
+    let err = mapped_buf.sync_range(15_000, 1_001, SyncMode::Sync).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_stats() -> Result<(), Box<dyn std::error::Error>> {
+    // SAFETY: All zero bytes are a valid `u64`
+    let mapped_buf = unsafe { MappedBuffer::<u64>::anonymous(16_000)? };
+    let stats = mapped_buf.stats()?;
+    assert_eq!(stats.total_pages, (16_000 * 8usize).div_ceil(page_size()));
+
+    mapped_buf.lock()?;
+    let stats = mapped_buf.stats()?;
+    assert_eq!(stats.resident_pages, stats.total_pages);
+
+    assert!(stats.minor_faults.is_none() && stats.major_faults.is_none());
+
+    let builder = MappedBufferBuilder::new().track_faults(true);
+    // SAFETY: All zero bytes are a valid `u64`
+    let mapped_buf = unsafe { builder.build_anon::<u64>(16_000)? };
+    let stats = mapped_buf.stats()?;
+
+    #[cfg(target_os = "linux")]
+    assert!(stats.minor_faults.is_some() && stats.major_faults.is_some());
+    #[cfg(not(target_os = "linux"))]
+    assert!(stats.minor_faults.is_none() && stats.major_faults.is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_from_file() -> Result<(), std::io::Error> {
+    use std::io::{Read, Seek};
+
+    let mut file = tempfile::tempfile()?;
+    // SAFETY: Any bytes are a valid `u8`
+    let mut mapped_buf = unsafe { MappedBuffer::<u8>::from_file(file.try_clone()?, 16_000)? };
+    mapped_buf[..5].copy_from_slice(b"bytes");
+    mapped_buf.flush()?;
+
+    let mut bytes = [0; 5];
+    file.rewind()?;
+    file.read_exact(&mut bytes)?;
+    assert_eq!(&bytes, b"bytes");
+    assert_eq!(file.metadata()?.len(), 16_000);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_prefetch() -> Result<(), Box<dyn std::error::Error>> {
+    // SAFETY: All zero bytes are a valid `u64`
+    let mapped_buf = unsafe { MappedBuffer::<u64>::anonymous(64_000)? };
+    mapped_buf.prefetch(16_000..32_000)?;
+    mapped_buf.prefetch(..)?;
+    mapped_buf.prefetch(64_000..)?;
+
+    let stats = mapped_buf.stats()?;
+    assert_eq!(stats.resident_pages, stats.total_pages);
+    assert!(matches!(mapped_buf.prefetch(..=64_000), Err(AdviseError::EINVAL)));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_lock_policy() -> Result<(), MapError> {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new_with(buf, LockPolicy::None)?;
+    assert_eq!(mapped_buf.receive(), &buf);
+
+    let mapped_buf = MappedBuffer::new_with(buf, LockPolicy::Eager)?;
+    let stats = mapped_buf.stats()?;
+    assert_eq!(stats.resident_pages, stats.total_pages);
+
+    match MappedBuffer::new_with(buf, LockPolicy::OnFault) {
+        Ok(_) | Err(MapError::Lock(LockError::ENOSYS)) => {}
+        Err(err) => return Err(err),
+    }
+
+    let err = MappedBuffer::new_with([0u8; 0], LockPolicy::Eager).err().unwrap();
+    assert!(matches!(err, MapError::Map(_)));
+
+    Ok(())
+}
+
+#[cfg(feature = "bytemuck")]
+#[test]
+pub fn mapper_pod() -> Result<(), std::io::Error> {
+    use std::io::Write;
+
+    let mapped_buf = MappedBuffer::new_pod([420u32; 16_000])?;
+    assert_eq!(mapped_buf[0], 420);
+
+    let mut file = tempfile::tempfile()?;
+    file.write_all(&[0xff; 64])?;
+    let mapped_buf = MappedBuffer::<u64>::from_file_pod(file, 8)?;
+    assert!(mapped_buf.iter().all(|&element| element == u64::MAX));
+
+    let mapped_buf = MappedBuffer::<f64>::anonymous_pod(16_000)?;
+    assert!(mapped_buf.iter().all(|&element| element == 0.0));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_release_from() -> Result<(), Box<dyn std::error::Error>> {
+    let page_size = page_size();
+    let builder = MappedBufferBuilder::new().shared(false);
+    // SAFETY: All zero bytes are a valid `u8`
+    let mut mapped_buf = unsafe { builder.build_anon::<u8>(4 * page_size)? };
+    mapped_buf.fill(42);
+
+    // SAFETY: All zero bytes are a valid `u8`
+    unsafe { mapped_buf.release_from(page_size + 1)? };
+    assert!(mapped_buf[..2 * page_size].iter().all(|&byte| byte == 42));
+    assert!(mapped_buf[2 * page_size..].iter().all(|&byte| byte == 0));
+
+    // SAFETY: All zero bytes are a valid `u8`
+    unsafe {
+        mapped_buf.release_from(4 * page_size)?;
+        let err = mapped_buf.release_from(4 * page_size + 1);
+        assert!(matches!(err, Err(AdviseError::EINVAL)));
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_release_from_shared() -> Result<(), Box<dyn std::error::Error>> {
+    let page_size = page_size();
+    let buf = vec![42u8; 4 * page_size];
+    let mut mapped_buf = MappedBuffer::new(&buf)?;
+
+    // SAFETY: The mapping is shared, so the released elements keep their values
+    unsafe { mapped_buf.release_from(page_size)? };
+    assert_eq!(mapped_buf.receive(), buf);
+
+    let mut mapped_buf = MappedBuffer::new([420; 16_000])?;
+    // SAFETY: The mapping is shared, so the released elements keep their values
+    unsafe { mapped_buf.release_from(0)? };
+    assert!(mapped_buf.iter().all(|&element| element == 420));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_make_readonly() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+    mapped_buf.make_readonly()?;
+    assert_eq!(mapped_buf[15_999], 420);
+
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| mapped_buf[0] = 69));
+    assert!(err.is_err());
+
+    mapped_buf.make_writable()?;
+    mapped_buf[0] = 69;
+    assert_eq!(mapped_buf[0], 69);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_read_write_at() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+
+    mapped_buf.write_at(0, 69);
+    mapped_buf.write_at(15_999, 42);
+    assert_eq!(mapped_buf.read_at(0), 69);
+    assert_eq!(mapped_buf.read_at(15_999), 42);
+    assert_eq!(mapped_buf[1..15_999], [420; 15_998]);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+pub fn mapper_read_at_out_of_range() {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf).unwrap();
+
+    mapped_buf.read_at(16_000);
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+pub fn mapper_write_at_out_of_range() {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf).unwrap();
+
+    mapped_buf.write_at(16_000, 69);
+}
+
+#[test]
+pub fn mapper_new_in() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("memguar-new-in-{}", std::process::id()));
+    std::fs::create_dir(&dir)?;
+    let mapped_buf = MappedBuffer::new_in([42u8; 16_000], &dir);
+
+    #[cfg(target_os = "linux")]
+    if let Ok(mapped_buf) = &mapped_buf {
+        let fd = mapped_buf.as_raw_fd().ok_or("no backing file")?;
+        let backing = std::fs::read_link(format!("/proc/self/fd/{fd}"))?;
+        assert!(backing.starts_with(&dir), "{backing:?}");
+    }
+
+    // The tempfile is unlinked right away
+    let entries = std::fs::read_dir(&dir)?.count();
+    std::fs::remove_dir(&dir)?;
+    assert_eq!(entries, 0);
+    assert_eq!(mapped_buf?.receive(), [42u8; 16_000]);
+
+    let missing = MappedBuffer::new_in([42u8; 16_000], &dir);
+    assert_eq!(missing.map(drop).map_err(|err| err.kind()), Err(std::io::ErrorKind::NotFound));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_with_capacity() -> Result<(), Box<dyn std::error::Error>> {
+    // SAFETY: All zero bytes are a valid `u64`
+    let mut mapped_buf = unsafe { MappedBuffer::<u64>::with_capacity(16_000)? };
+    assert_eq!(mapped_buf.len(), 16_000);
+    assert!(mapped_buf.iter().all(|&elem| elem == 0));
+    assert!(mapped_buf.as_raw_fd().is_some());
+
+    mapped_buf[15_999] = 42;
+    assert_eq!(mapped_buf.read_at(15_999), 42);
+
+    // SAFETY: An empty buffer holds no zeroed elements
+    let mut empty_buf = unsafe { MappedBuffer::<u64>::with_capacity(0)? };
+    assert!(empty_buf.is_empty());
+    // SAFETY: `u64` has no padding bytes
+    assert!(unsafe { empty_buf.as_bytes() }.is_empty());
+    assert_eq!(empty_buf.capacity_bytes(), page_size());
+
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: All zero bytes are a valid `u64`
+        unsafe { empty_buf.resize(16)? };
+        assert_eq!(empty_buf.receive(), [0; 16]);
+        assert_eq!(empty_buf.capacity_bytes(), page_size());
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_clone_empty() -> Result<(), std::io::Error> {
+    // SAFETY: An empty buffer holds no zeroed elements
+    let mut empty_buf = unsafe { MappedBuffer::<u64>::with_capacity(0)? };
+    empty_buf.set_zeroize(true);
+
+    let cloned_buf = empty_buf.try_clone()?;
+    assert!(cloned_buf.is_empty());
+    assert_eq!(cloned_buf, empty_buf);
+    assert!(empty_buf.clone().is_empty());
+
+    // The clone of a read-only buffer is read-only too
+    empty_buf.make_readonly()?;
+    let mut cloned_buf = empty_buf.clone();
+    assert!(cloned_buf.is_empty());
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        cloned_buf.receive_mut();
+    }));
+    assert!(err.is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_addr() -> Result<(), Box<dyn std::error::Error>> {
+    let buf = [42u8; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+
+    assert_eq!(mapped_buf.addr() % page_size(), 0);
+    assert_eq!(mapped_buf.addr(), mapped_buf.as_ptr() as usize);
+    assert_eq!(format!("{mapped_buf:p}"), format!("{:p}", mapped_buf.as_ptr()));
+
+    let path = std::env::temp_dir().join(format!("memguar-addr-{}", std::process::id()));
+    std::fs::write(&path, [42u8; 16_000])?;
+    // SAFETY: Any bytes are a valid `u8`
+    let region = unsafe { MappedBuffer::<u8>::map_region(&path, 5_000, 8_000) };
+    std::fs::remove_file(&path)?;
+    let region = region?;
+
+    assert_eq!(region.addr() % page_size(), 0);
+    assert_eq!(region.as_ptr() as usize - region.addr(), 5_000 % page_size());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_as_raw_fd() -> Result<(), Box<dyn std::error::Error>> {
+    let buf = [42u8; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    let fd = mapped_buf.as_raw_fd().ok_or("no backing file")?;
+
+    // SAFETY: FFI. `fd` stays open while `mapped_buf` is alive
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    assert_ne!(flags, -1);
+    assert_eq!(mapped_buf.try_clone()?.as_raw_fd().map(|clone_fd| clone_fd == fd), Some(false));
+
+    // SAFETY: All zero bytes are a valid `u8`
+    assert!(unsafe { MappedBuffer::<u8>::anonymous(16_000)? }.as_raw_fd().is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_backing_fd() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Read;
+    use std::os::fd::AsRawFd;
+
+    let buf = [42u8; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    let fd = mapped_buf.backing_fd().ok_or("no backing file")?;
+    assert_eq!(Some(fd.as_raw_fd()), mapped_buf.as_raw_fd());
+
+    // A duplicate shares the file (and its offset), but not the descriptor
+    let mut file = std::fs::File::from(fd.try_clone_to_owned()?);
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    assert_eq!(contents, buf);
+    drop(file);
+    assert_eq!(mapped_buf.receive(), buf);
+
+    // SAFETY: All zero bytes are a valid `u8`
+    assert!(unsafe { MappedBuffer::<u8>::anonymous(16_000)? }.backing_fd().is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_prefetch_async() -> Result<(), Box<dyn std::error::Error>> {
+    let buf = [42u8; 16_000];
+    let mapped_buf = MappedBuffer::new(buf)?;
+
+    mapped_buf.prefetch_async(..)?;
+    mapped_buf.prefetch_async(4_000..8_000)?;
+    mapped_buf.prefetch_async(16_000..)?;
+    assert!(matches!(mapped_buf.prefetch_async(8_000..16_001), Err(AdviseError::EINVAL)));
+
+    // SAFETY: All zero bytes are a valid `u8`
+    let anon_buf = unsafe { MappedBuffer::<u8>::anonymous(16_000)? };
+    anon_buf.prefetch_async(..)?;
+
+    let path = std::env::temp_dir().join(format!("memguar-prefetch-{}", std::process::id()));
+    std::fs::write(&path, [42u8; 16_000])?;
+    // SAFETY: Any bytes are a valid `u8`
+    let region = unsafe { MappedBuffer::<u8>::map_region(&path, 5_000, 8_000) };
+    std::fs::remove_file(&path)?;
+    let region = region?;
+
+    region.prefetch_async(1_000..)?;
+    assert_eq!(region.receive(), [42u8; 8_000]);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_split_at() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+    let (left, right) = mapped_buf.split_at_mut(6_000);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| left.fill(4));
+        scope.spawn(|| right.fill(2));
+    });
+
+    let (left, right) = mapped_buf.split_at(6_000);
+    assert_eq!(left, [4; 6_000]);
+    assert_eq!(right, [2; 10_000]);
+
+    let (left, right) = mapped_buf.split_at(16_000);
+    assert_eq!((left.len(), right.len()), (16_000, 0));
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "mid > len")]
+pub fn mapper_split_at_out_of_range() {
+    let buf = [420; 16_000];
+    let mapped_buf = MappedBuffer::new(buf).unwrap();
+
+    mapped_buf.split_at(16_001);
+}
+
+#[test]
+pub fn mapper_fill() -> Result<(), std::io::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("buf.bin");
+    // SAFETY: Any bytes are a valid `u64`
+    let mut mapped_buf = unsafe { MappedBuffer::<u64>::from_path(&path, 16_000)? };
+    mapped_buf.fill(420);
+    mapped_buf.copy_from_slice(&vec![69; 16_000]);
+    mapped_buf[..3].copy_from_slice(&[4, 2, 0]);
+    mapped_buf.flush()?;
+    drop(mapped_buf);
+
+    // SAFETY: Any bytes are a valid `u64`
+    let mapped_buf = unsafe { MappedBuffer::<u64>::from_path(&path, 16_000)? };
+    assert_eq!(mapped_buf[..4], [4, 2, 0, 69]);
+    assert!(mapped_buf[3..].iter().all(|&element| element == 69));
+
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+pub fn mapper_copy_from_slice_len() {
+    // SAFETY: All zero bytes are a valid `u64`
+    let mut mapped_buf = unsafe { MappedBuffer::<u64>::anonymous(16_000).unwrap() };
+    mapped_buf.copy_from_slice(&[420; 8_000]);
+}
+
+#[test]
+pub fn mapper_advised_chunks() -> Result<(), AdviseError> {
+    let buf: Vec<u64> = (0..16_000).collect();
+    let mapped_buf = MappedBuffer::new(&buf).unwrap();
+    let chunks = mapped_buf.advised_chunks(3_000);
+    assert_eq!(chunks.size_hint(), (6, Some(6)));
+
+    let mut sum = 0;
+
+    for chunk in chunks {
+        sum += chunk?.iter().sum::<u64>();
+    }
+
+    assert_eq!(sum, buf.iter().sum());
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn mapper_memfd() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new_memfd(buf)?;
+    assert_eq!(mapped_buf.receive(), &buf);
+
+    // SAFETY: All zero bytes are a valid `i32`
+    unsafe { mapped_buf.resize(32_000)? };
+    assert_eq!(mapped_buf[16_000..], [0; 16_000]);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn mapper_seal() -> Result<(), std::io::Error> {
+    use crate::mapper::Seals;
+
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new_memfd(buf)?;
+    mapped_buf.seal(Seals::GROW | Seals::SHRINK)?;
+    // SAFETY: All zero bytes are a valid `i32`
+    assert!(unsafe { mapped_buf.resize(32_000) }.is_err());
+
+    let err = mapped_buf.seal(Seals::WRITE).err().unwrap();
+    assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+    mapped_buf.seal(Seals::SEAL)?;
+
+    assert!(MappedBuffer::new(buf)?.seal(Seals::GROW).is_err());
+    // SAFETY: All zero bytes are a valid `u64`
+    assert!(unsafe { MappedBuffer::<u64>::anonymous(16_000)? }.seal(Seals::GROW).is_err());
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_zero_size() {
+    let err = MappedBuffer::new([0u8; 0]).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let err = ByteBuffer::new_bytes(0).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+pub fn mapper_overflow() {
+    // SAFETY: All zero bytes are a valid `u64`
+    let err = unsafe { MappedBuffer::<u64>::anonymous(usize::MAX / 4).err() }.unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    // SAFETY: Any bytes are a valid `u64`
+    let err = unsafe { MappedBuffer::<u64>::map_region("buf.bin", u64::MAX - 8, 16) };
+    let err = err.err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+pub fn mapper_zeroize() -> Result<(), std::io::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("buf.bin");
+    // SAFETY: Any bytes are a valid `u64`
+    let mut mapped_buf = unsafe { MappedBuffer::<u64>::from_path(&path, 16_000)? };
+    mapped_buf.fill(420);
+    mapped_buf.set_zeroize(true);
+    drop(mapped_buf);
+
+    // SAFETY: Any bytes are a valid `u64`
+    let mapped_buf = unsafe { MappedBuffer::<u64>::from_path(&path, 16_000)? };
+    assert!(mapped_buf.iter().all(|&x| x == 0));
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_zeroize_file() -> Result<(), std::io::Error> {
+    use std::io::{Read, Seek};
+
+    let mut file = tempfile::tempfile()?;
+    // SAFETY: Any bytes are a valid `u8`
+    let mut mapped_buf = unsafe { MappedBuffer::<u8>::from_file(file.try_clone()?, 16_000)? };
+    mapped_buf.fill(42);
+    mapped_buf.set_zeroize(true);
+    drop(mapped_buf);
+
+    let mut contents = Vec::new();
+    file.rewind()?;
+    file.read_to_end(&mut contents)?;
+    assert_eq!(contents, [0; 16_000]);
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("buf.bin");
+    std::fs::write(&path, [42u8; 16_000])?;
+    // SAFETY: Any bytes are a valid `u8`
+    let mut region = unsafe { MappedBuffer::<u8>::map_region(&path, 5_000, 8_000)? };
+    region.set_zeroize(true);
+    drop(region);
+
+    // Only the region is overwritten, not the rest of its first and last pages
+    let contents = std::fs::read(&path)?;
+    assert_eq!(contents[..5_000], [42; 5_000]);
+    assert_eq!(contents[5_000..13_000], [0; 8_000]);
+    assert_eq!(contents[13_000..], [42; 3_000]);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn mapper_resize() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+    // SAFETY: All zero bytes are a valid `i32`
+    unsafe { mapped_buf.resize(32_000)? };
+
+    assert_eq!(mapped_buf.len(), 32_000);
+    assert_eq!(mapped_buf[15_999], 420);
+    assert_eq!(mapped_buf[16_000], 0);
+
+    // SAFETY: Shrinking adds no elements
+    unsafe { mapped_buf.resize(8_000)? };
+    assert_eq!(mapped_buf.len(), 8_000);
+    // SAFETY: All zero bytes are a valid `u64`
+    assert!(unsafe { MappedBuffer::<u64>::anonymous(16_000)?.resize(32_000) }.is_err());
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn mapper_resize_shrink_grow() -> Result<(), std::io::Error> {
+    let buf = [420; 4096];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+    // SAFETY: All zero bytes are a valid `i32`
+    unsafe {
+        mapped_buf.resize(1024)?;
+        mapped_buf.resize(8192)?;
+    }
+
+    assert_eq!(mapped_buf[1023], 420);
+    assert_eq!(mapped_buf[2000], 0);
+    assert_eq!(mapped_buf[1024..], [0; 7168]);
+
+    let mut mapped_buf = MappedBufferBuilder::new()
+        .round_to_pages(true)
+        .writable(false)
+        .build_from([42u8; 100])?;
+    // SAFETY: All zero bytes are a valid `u8`
+    unsafe {
+        mapped_buf.resize(10)?;
+        mapped_buf.resize(100)?;
+    }
+
+    assert!(!mapped_buf.writable);
+    assert_eq!(mapped_buf[..10], [42; 10]);
+    assert_eq!(mapped_buf[10..], [0; 90]);
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn mapper_capacity() -> Result<(), std::io::Error> {
+    let page_size = page_size();
+    let buf = [42u8; 100];
+    let mapped_buf = MappedBuffer::new(buf)?;
+    assert_eq!(mapped_buf.capacity_bytes(), 100);
+
+    let mut mapped_buf = MappedBufferBuilder::new()
+        .round_to_pages(true)
+        .build_from(buf)?;
+    assert_eq!(mapped_buf.len(), 100);
+    // SAFETY: `u8` has no padding bytes
+    assert_eq!(unsafe { mapped_buf.as_bytes() }.len(), 100);
+    assert_eq!(mapped_buf.capacity_bytes(), page_size);
+
+    let ptr = mapped_buf.as_ptr();
+    // SAFETY: All zero bytes are a valid `u8`
+    unsafe { mapped_buf.resize(page_size)? };
+    assert_eq!(mapped_buf.as_ptr(), ptr);
+    assert_eq!(mapped_buf[99], 42);
+    assert_eq!(mapped_buf[100..], vec![0; page_size - 100]);
+
+    mapped_buf.fill(69);
+    // SAFETY: All zero bytes are a valid `u8`
+    unsafe {
+        mapped_buf.resize(10)?;
+        mapped_buf.resize(20)?;
+    }
+    assert_eq!(mapped_buf.capacity_bytes(), page_size);
+    assert_eq!(mapped_buf[..10], [69; 10]);
+    assert_eq!(mapped_buf[10..], [0; 10]);
+
+    let builder = MappedBufferBuilder::new().round_to_pages(true);
+    // SAFETY: All zero bytes are a valid `u64`
+    let mut mapped_buf = unsafe { builder.build_anon::<u64>(10)? };
+    // SAFETY: All zero bytes are a valid `u64`
+    unsafe {
+        mapped_buf.resize(page_size / 8)?;
+        assert!(mapped_buf.resize(page_size / 8 + 1).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_region() -> Result<(), std::io::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("buf.bin");
+    // SAFETY: Any bytes are a valid `u64`
+    let mut mapped_buf = unsafe { MappedBuffer::<u64>::from_path(&path, 16_000)? };
+    mapped_buf[10_000] = 420;
+    drop(mapped_buf);
+
+    // SAFETY: Any bytes are a valid `u64`
+    let mapped_buf = unsafe { MappedBuffer::<u64>::map_region(&path, 10_000 * 8, 16)? };
+    assert_eq!(mapped_buf.len(), 16);
+    assert_eq!(mapped_buf[0], 420);
+
+    // SAFETY: Any bytes are a valid `u64`
+    let err = unsafe { MappedBuffer::<u64>::map_region(&path, 15_990 * 8, 16) };
+    assert_eq!(err.err().unwrap().kind(), std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(std::fs::metadata(&path)?.len(), 16_000 * 8);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_index() -> Result<(), std::io::Error> {
+    let buf = [420; 16_000];
+    let mut mapped_buf = MappedBuffer::new(buf)?;
+    mapped_buf[0] = 69;
+
+    assert_eq!(mapped_buf[0], 69);
+    assert_eq!(mapped_buf[1..3], [420, 420]);
+    assert_eq!(mapped_buf.get(15_999), Some(&420));
+    assert_eq!(mapped_buf.get(16_000), None);
+    assert!(mapped_buf.get_mut(16_000).is_none());
+
+    Ok(())
+}
+
+#[test]
+pub fn guard() -> Result<(), GuardError> {
+    let buf = vec![420; 16_000];
+    let mut guarded_buf = Guard::new(buf)?;
+    guarded_buf.get_mut()[0] = 69;
+
+    assert_eq!(guarded_buf.get()[0], 69);
+
+    Ok(())
+}
+
+#[test]
+pub fn mapper_bytes() -> Result<(), std::io::Error> {
+    let mut mapped_buf = ByteBuffer::new_bytes(16_000)?;
+    mapped_buf[..5].copy_from_slice(b"bytes");
+
+    // SAFETY: `u8` has no padding bytes
+    assert_eq!(&unsafe { mapped_buf.as_bytes() }[..5], b"bytes");
+    assert_eq!(mapped_buf.len(), 16_000);
+
+    Ok(())
+}
+
+#[test]
+pub fn page_size_cached() {
+    let size = page_size();
+
+    assert!(size.is_power_of_two());
+    assert_eq!(size, page_size());
+}
@@ -0,0 +1,21 @@
+/// Reports the outcome of a syscall as a `tracing` event along with the given fields,
+/// `trace!` on success and `warn!` on failure.
+///
+/// Takes the already built `Result`, since emitting an event may overwrite `errno`.
+/// Without the `tracing` feature only `$result` is evaluated and no event is emitted.
+#[cfg(feature = "tracing")]
+macro_rules! trace_syscall {
+    ($syscall:literal, $result:expr, $($field:tt)*) => {
+        match $result {
+            Ok(_) => tracing::trace!(syscall = $syscall, $($field)*, "syscall succeeded"),
+            Err(err) => tracing::warn!(syscall = $syscall, $($field)*, error = %err, "syscall failed"),
+        }
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_syscall {
+    ($syscall:literal, $result:expr, $($field:tt)*) => {
+        let _ = $result;
+    };
+}
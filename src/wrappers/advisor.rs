@@ -1,9 +1,13 @@
-use std::convert::AsMut;
-use std::marker::PhantomData;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+use core::ops::Range;
 
 use libc::{c_int, c_void, posix_madvise};
 
-use crate::wrappers::advisor::Advise::DontNeed;
+use crate::errno::Errno;
+use crate::page::page_size;
 
 /// A wrapper-struct `Adviser` that is used to advise the system
 /// about the expected behavior of memory access patterns of the buffer's page.
@@ -20,54 +24,583 @@ use crate::wrappers::advisor::Advise::DontNeed;
 ///     .syscall_advise(DontNeed)
 ///     .unwrap();
 /// ```
-#[repr(transparent)]
-pub struct Adviser<C: AsMut<[T]>, T> {
+pub struct Adviser<C, T> {
     pub buf: C,
+    slice: fn(&mut C) -> &[T],
+    drop_advise: Option<Advise>,
+    last_advise: Option<Advise>,
     item_type: PhantomData<T>,
 }
 
-impl<C: AsMut<[T]>, T> Adviser<C, T> {
-    pub fn new(buf: C) -> Self {
+impl<C, T> Adviser<C, T> {
+    pub fn new(buf: C) -> Self
+    where
+        C: AsMut<[T]>,
+    {
+        Self::with_slice(buf, |buf| C::as_mut(buf))
+    }
+
+    /// Creates an `Adviser` over a buffer that is only readable, such as an `Arc<[T]>`,
+    /// since the non-destructive hints only need the address and length of the buffer.
+    /// Linux hints (`advise_linux`) still require mutable access.
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use memguar::advisor::Advise::WillNeed;
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf: Arc<[i32]> = Arc::from([420; 16_000]);
+    /// let mut advised_buf = Adviser::new_readonly(buf);
+    ///
+    /// advised_buf
+    ///     .syscall_advise(WillNeed)
+    ///     .unwrap();
+    /// ```
+    pub fn new_readonly(buf: C) -> Self
+    where
+        C: AsRef<[T]>,
+    {
+        Self::with_slice(buf, |buf| C::as_ref(buf))
+    }
+
+    fn with_slice(buf: C, slice: fn(&mut C) -> &[T]) -> Self {
         Self {
             buf,
+            slice,
+            drop_advise: None,
+            last_advise: None,
             item_type: PhantomData,
         }
     }
 
+    /// Sets the hint that is applied to the buffer's page when the `Adviser` drops.
+    /// By default nothing is advised on drop.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::DontNeed;
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf = [420; 16_000];
+    /// let _advised_buf = Adviser::new(buf)
+    ///     .with_drop_advise(DontNeed);
+    /// ```
+    pub fn with_drop_advise(mut self, advise: Advise) -> Self {
+        self.drop_advise = Some(advise);
+        self
+    }
+
+    /// Returns the hint most recently applied to the buffer's page by `syscall_advise`
+    /// (including the ones applied by `advise_scoped` and its guard),
+    /// or `None` if nothing was applied yet.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::DontNeed;
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf = [420; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// advised_buf
+    ///     .syscall_advise(DontNeed)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(advised_buf.last_advise(), Some(DontNeed));
+    /// ```
+    pub fn last_advise(&self) -> Option<Advise> {
+        self.last_advise
+    }
+
     /// If `syscall_advise` is successful, it allows the system to apply specific optimizations to the page,
     /// based on the specified flag, such as moving it to the swap file
     /// or merging it with adjacent pages.
+    /// The advised range starts at the beginning of the page containing the buffer,
+    /// since `posix_madvise` requires a page aligned address.
     pub fn syscall_advise(&mut self, advise: Advise) -> Result<(), AdviseError> {
-        let buf = self.buf.as_mut();
-        assert!(size_of_val(buf) > 0, "Zero size buffer");
-        let ptr = buf.as_mut_ptr() as *mut c_void;
-        let len = size_of_val(buf);
-        // SAFETY: FFI. Valid ptr (*mut c_void) and len + processed `posix_madvise` result
+        let (ptr, len) = self.pages(false);
+        // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the buffer's pages
+        // + processed `posix_madvise` result
         let result = unsafe {
             posix_madvise(ptr, len, advise as c_int)
         };
+        let result = match result {
+            0 => Ok(()),
+            result => Err(AdviseError::from(result)),
+        };
 
-        match result {
+        trace_syscall!("posix_madvise", &result, ptr = ?ptr, len, advise = ?advise);
+
+        if result.is_ok() {
+            self.last_advise = Some(advise);
+        }
+
+        result
+    }
+
+    /// If `advise_range` is successful, the hint is applied to the pages holding
+    /// `len` elements starting at the element `start`, rounded to page boundaries.
+    /// Returns `EINVAL` if the range is out of the buffer's bounds.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::WillNeed;
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// advised_buf
+    ///     .advise_range(4_000, 8_000, WillNeed)
+    ///     .unwrap();
+    /// ```
+    pub fn advise_range(
+        &mut self,
+        start: usize,
+        len: usize,
+        advise: Advise,
+    ) -> Result<(), AdviseError> {
+        let (ptr, len) = self.range(start, len, false)?;
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the range's pages
+        // + processed `posix_madvise` result
+        let result = unsafe {
+            posix_madvise(ptr, len, advise as c_int)
+        };
+        let result = match result {
             0 => Ok(()),
             result => Err(AdviseError::from(result)),
+        };
+
+        trace_syscall!("posix_madvise", &result, ptr = ?ptr, len, advise = ?advise);
+        result
+    }
+
+    /// If `advise_many` is successful, the hint is applied to the pages holding each of
+    /// the `(start, len)` element ranges. Overlapping and adjacent ranges are coalesced
+    /// by page first, so as few `posix_madvise` calls as possible are issued.
+    /// Returns `EINVAL` if any range is out of the buffer's bounds (before advising anything),
+    /// otherwise the first error of `posix_madvise`.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::WillNeed;
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// advised_buf
+    ///     .advise_many(&[(0, 100), (50, 200), (8_000, 1_000)], WillNeed)
+    ///     .unwrap();
+    /// ```
+    pub fn advise_many(
+        &mut self,
+        ranges: &[(usize, usize)],
+        advise: Advise,
+    ) -> Result<(), AdviseError> {
+        let page_size = page_size();
+        let mut spans = Vec::with_capacity(ranges.len());
+
+        for &(start, len) in ranges {
+            let (ptr, len) = self.range(start, len, false)?;
+
+            if len > 0 {
+                let start = ptr as usize;
+                spans.push((start, (start + len).next_multiple_of(page_size)));
+            }
+        }
+
+        spans.sort_unstable();
+
+        let mut pages: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+
+        for (start, end) in spans {
+            match pages.last_mut() {
+                Some((_, last_end)) if start <= *last_end => *last_end = end.max(*last_end),
+                _ => pages.push((start, end)),
+            }
+        }
+
+        let advise = advise as c_int;
+
+        for (start, end) in pages {
+            // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the ranges' pages
+            // + processed `posix_madvise` result
+            let result = unsafe {
+                posix_madvise(start as *mut c_void, end - start, advise)
+            };
+            let result = match result {
+                0 => Ok(()),
+                result => Err(AdviseError::from(result)),
+            };
+
+            trace_syscall!("posix_madvise", &result, ptr = start, len = end - start, advise);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// If `advise_all` is successful, each hint is applied to the pages holding its range
+    /// of elements with `advise_range`, in order, so a known access plan is issued in one call.
+    /// On failure the remaining hints are skipped and the index of the failed one is returned
+    /// along with the error, `EINVAL` if its range is reversed or out of the buffer's bounds.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::{DontNeed, Sequential};
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// advised_buf
+    ///     .advise_all(&[(0..8_000, Sequential), (8_000..16_000, DontNeed)])
+    ///     .unwrap();
+    /// ```
+    pub fn advise_all(
+        &mut self,
+        hints: &[(Range<usize>, Advise)],
+    ) -> Result<(), (usize, AdviseError)> {
+        for (index, (range, advise)) in hints.iter().enumerate() {
+            let len = range.end.checked_sub(range.start).ok_or((index, AdviseError::EINVAL))?;
+
+            self.advise_range(range.start, len, *advise)
+                .map_err(|err| (index, err))?;
         }
+
+        Ok(())
+    }
+
+    /// If `advise_scoped` is successful, the hint is applied to the buffer's page
+    /// until the returned guard drops, which then re-applies `Normal`.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Advise::Sequential;
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// {
+    ///     let _guard = advised_buf
+    ///         .advise_scoped(Sequential)
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn advise_scoped(&mut self, advise: Advise) -> Result<AdviseGuard<'_, C, T>, AdviseError> {
+        self.advise_scoped_with(advise, Advise::Normal)
+    }
+
+    /// Same as `advise_scoped`, but the guard re-applies `restore` on drop.
+    pub fn advise_scoped_with(
+        &mut self,
+        advise: Advise,
+        restore: Advise,
+    ) -> Result<AdviseGuard<'_, C, T>, AdviseError> {
+        self.syscall_advise(advise)?;
+
+        Ok(AdviseGuard {
+            adviser: self,
+            restore: Some(restore),
+        })
+    }
+
+    /// If `advise_linux` is successful, it applies a Linux-specific hint to the buffer's page
+    /// via the raw `madvise`, such as excluding it from core dumps.
+    ///
+    /// The hints that discard the contents of the pages (`Free`, `Remove`) return `EINVAL`,
+    /// use `advise_linux_destructive` for them. `DontFork` only covers the pages that lie
+    /// entirely within the buffer, so memory sharing a page with the buffer is unaffected.
+    ///
+    /// The reclaim hints (`Cold`, `PageOut`) return `ENOSYS` if the running kernel predates them,
+    /// instead of the `EINVAL` it reports for unknown hints, which `PageOut` also returns
+    /// for locked pages.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::{AdviseError, Adviser, LinuxAdvise};
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// match advised_buf.advise_linux(LinuxAdvise::Cold) {
+    ///     Ok(()) | Err(AdviseError::ENOSYS) => {}
+    ///     Err(err) => panic!("{err}"),
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn advise_linux(&mut self, advise: LinuxAdvise) -> Result<(), AdviseError>
+    where
+        C: AsMut<[T]>,
+    {
+        if matches!(advise, LinuxAdvise::Free | LinuxAdvise::Remove) {
+            return Err(AdviseError::EINVAL);
+        }
+
+        self.madvise(advise)
+    }
+
+    /// Same as `advise_linux`, but also applies the hints that discard the contents
+    /// of the buffer's pages: after `Free` or `Remove`, private anonymous pages
+    /// (such as those of a `Vec`) may read back as zero bytes.
+    /// Like `DontFork`, they only cover the pages that lie entirely within the buffer,
+    /// so memory sharing a page with the buffer is never discarded.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::{Adviser, LinuxAdvise};
+    ///
+    /// let buf = vec![420u64; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// // SAFETY: All zero bytes are a valid `u64`
+    /// unsafe { advised_buf.advise_linux_destructive(LinuxAdvise::Free) }
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// For `Free` and `Remove`, all zero bytes must be a valid `T`, which holds for integers,
+    /// floats and raw pointers, but not for references, `NonZero*`, `char` or most enums.
+    #[cfg(target_os = "linux")]
+    pub unsafe fn advise_linux_destructive(
+        &mut self,
+        advise: LinuxAdvise,
+    ) -> Result<(), AdviseError>
+    where
+        C: AsMut<[T]>,
+    {
+        self.madvise(advise)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn madvise(&mut self, advise: LinuxAdvise) -> Result<(), AdviseError> {
+        let destructive = matches!(
+            advise,
+            LinuxAdvise::Free | LinuxAdvise::Remove | LinuxAdvise::DontFork
+        );
+        let (ptr, len) = self.pages(destructive);
+
+        if len == 0 {
+            return Ok(());
+        }
+
+        let reclaim = matches!(advise, LinuxAdvise::Cold | LinuxAdvise::PageOut);
+        let advise = advise as c_int;
+        // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the buffer's pages
+        // + processed `madvise` result
+        let result = unsafe {
+            libc::madvise(ptr, len, advise)
+        };
+        let result = match result {
+            0 => Ok(()),
+            _ => match AdviseError::last_os_error() {
+                AdviseError::EINVAL if reclaim && !supported(ptr, advise) => {
+                    Err(AdviseError::ENOSYS)
+                }
+                err => Err(err),
+            },
+        };
+
+        trace_syscall!("madvise", &result, ptr = ?ptr, len, advise);
+        result
+    }
+
+    /// If `set_fork_inherit` is successful, the buffer's pages are inherited by child processes
+    /// created by `fork` (`MADV_DOFORK`, the default) or not (`MADV_DONTFORK`),
+    /// so a forked worker neither doubles the memory pressure nor sees the buffer's data.
+    /// Like `DontFork`, it only covers the pages that lie entirely within the buffer.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::Adviser;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// advised_buf
+    ///     .set_fork_inherit(false)
+    ///     .unwrap();
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn set_fork_inherit(&mut self, inherit: bool) -> Result<(), AdviseError>
+    where
+        C: AsMut<[T]>,
+    {
+        match inherit {
+            true => self.advise_linux(LinuxAdvise::DoFork),
+            false => self.advise_linux(LinuxAdvise::DontFork),
+        }
+    }
+
+    /// If `set_huge_pages` is successful, the kernel is allowed (`MADV_HUGEPAGE`)
+    /// or forbidden (`MADV_NOHUGEPAGE`) to back the buffer's pages with transparent huge pages,
+    /// which gives most of the TLB benefit of `MAP_HUGETLB` without reserving huge pages up front.
+    /// Only the aligned huge pages that lie within the region are collapsed.
+    /// Returns `EINVAL` if the kernel is built without transparent huge pages.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::advisor::{AdviseError, Adviser};
+    ///
+    /// let buf = vec![420u64; 1_000_000];
+    /// let mut advised_buf = Adviser::new(buf);
+    ///
+    /// match advised_buf.set_huge_pages(true) {
+    ///     Ok(()) | Err(AdviseError::EINVAL) => {}
+    ///     Err(err) => panic!("{err}"),
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn set_huge_pages(&mut self, enabled: bool) -> Result<(), AdviseError>
+    where
+        C: AsMut<[T]>,
+    {
+        match enabled {
+            true => self.advise_linux(LinuxAdvise::HugePage),
+            false => self.advise_linux(LinuxAdvise::NoHugePage),
+        }
+    }
+
+    /// Page aligned range of the buffer. With `inner` only the pages lying entirely
+    /// within the buffer are included, otherwise the range is extended to the
+    /// boundaries of the pages containing the buffer.
+    fn pages(&mut self, inner: bool) -> (*mut c_void, usize) {
+        let buf = (self.slice)(&mut self.buf);
+        assert!(size_of_val(buf) > 0, "Zero size buffer");
+        let len = buf.len();
+
+        self.range(0, len, inner)
+            .expect("The whole buffer is always in bounds")
+    }
+
+    /// Page aligned range of `len` elements starting at the element `start`, see `pages`.
+    /// Returns `EINVAL` if the range is out of the buffer's bounds.
+    fn range(
+        &mut self,
+        start: usize,
+        len: usize,
+        inner: bool,
+    ) -> Result<(*mut c_void, usize), AdviseError> {
+        let buf = (self.slice)(&mut self.buf);
+        let buf = match start.checked_add(len) {
+            Some(end) if end <= buf.len() => &buf[start..end],
+            _ => return Err(AdviseError::EINVAL),
+        };
+        let page_size = page_size();
+        let addr = buf.as_ptr() as usize;
+        let end = addr + size_of_val(buf);
+        let (start, end) = match inner {
+            true => (addr.next_multiple_of(page_size), end - end % page_size),
+            false => (addr - addr % page_size, end),
+        };
+
+        Ok((start as *mut c_void, end.saturating_sub(start)))
     }
 }
 
-impl<C: AsMut<[T]>, T> Drop for Adviser<C, T> {
+impl<C, T> Drop for Adviser<C, T> {
     fn drop(&mut self) {
-        self.syscall_advise(DontNeed)
-            .expect("Cant give advise while dropping")
+        if let Some(advise) = self.drop_advise.take() {
+            // Advise errors can't be reported from `drop`
+            let _ = self.syscall_advise(advise);
+        }
     }
 }
+
+/// If `advise` is successful, the hint is applied to the pages of a borrowed buffer,
+/// same as `Adviser::syscall_advise`, but without wrapping (and owning) the buffer.
+/// A zero size buffer has no pages, so there is nothing to advise.
+/// # Examples
+///
+/// ```
+/// use memguar::advisor::Advise::WillNeed;
+/// use memguar::advisor::advise;
+///
+/// let mut buf = vec![420; 16_000];
+///
+/// advise(&buf, WillNeed).unwrap();
+/// buf[0] = 69;
+/// ```
+pub fn advise<T>(buf: &[T], advise: Advise) -> Result<(), AdviseError> {
+    if size_of_val(buf) == 0 {
+        return Ok(());
+    }
+
+    Adviser::new_readonly(buf).syscall_advise(advise)
+}
+
+/// Whether the running kernel knows the `madvise` hint, since it validates the hint
+/// before the range and a zero length range at the page aligned `ptr` is otherwise a no-op.
+#[cfg(target_os = "linux")]
+fn supported(ptr: *mut c_void, advise: c_int) -> bool {
+    // SAFETY: FFI. Page aligned ptr (*mut c_void) and zero len, nothing is advised
+    // + processed `madvise` result
+    let result = unsafe {
+        libc::madvise(ptr, 0, advise)
+    };
+
+    result == 0
+}
+
+/// A guard returned by `Adviser::advise_scoped`, that re-applies
+/// the restore hint to the buffer's page when dropped.
+#[must_use]
+pub struct AdviseGuard<'a, C, T> {
+    adviser: &'a mut Adviser<C, T>,
+    restore: Option<Advise>,
+}
+
+impl<C, T> Drop for AdviseGuard<'_, C, T> {
+    fn drop(&mut self) {
+        if let Some(restore) = self.restore.take() {
+            // Restore errors can't be reported from `drop`
+            let _ = self.adviser.syscall_advise(restore);
+        }
+    }
+}
+
 /// Advises for page
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(i32)]
 pub enum Advise {
-    WillNeed = 3,
-    DontNeed = 4,
+    Normal = libc::POSIX_MADV_NORMAL,
+    Random = libc::POSIX_MADV_RANDOM,
+    Sequential = libc::POSIX_MADV_SEQUENTIAL,
+    WillNeed = libc::POSIX_MADV_WILLNEED,
+    DontNeed = libc::POSIX_MADV_DONTNEED,
 }
+/// Linux-specific advises for page, applied via `advise_linux`
+#[cfg(target_os = "linux")]
+#[repr(i32)]
+pub enum LinuxAdvise {
+    Free = libc::MADV_FREE,
+    Remove = libc::MADV_REMOVE,
+    DontDump = libc::MADV_DONTDUMP,
+    DoDump = libc::MADV_DODUMP,
+    HugePage = libc::MADV_HUGEPAGE,
+    NoHugePage = libc::MADV_NOHUGEPAGE,
+    Mergeable = libc::MADV_MERGEABLE,
+    DontFork = libc::MADV_DONTFORK,
+    DoFork = libc::MADV_DOFORK,
+    /// Deactivates the pages, so they are reclaimed first under memory pressure (Linux 5.4+)
+    Cold = libc::MADV_COLD,
+    /// Reclaims the pages right away, writing them to the swap or their file (Linux 5.4+)
+    PageOut = libc::MADV_PAGEOUT,
+}
+
 /// Parsed types of `syscall_advise` errors
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum AdviseError {
     EFAULT,
     EINVAL,
@@ -76,14 +609,64 @@ pub enum AdviseError {
     EUNIM(c_int),
 }
 
+impl AdviseError {
+    /// Fallible counterpart of `From<c_int>` that returns `None` for `0`,
+    /// since it means success rather than an error.
+    /// (`TryFrom<c_int>` can't be implemented alongside `From<c_int>`)
+    pub fn try_from_errno(err: c_int) -> Option<Self> {
+        match err {
+            0 => None,
+            err => Some(AdviseError::from(err)),
+        }
+    }
+
+    /// Returns the raw `errno` value this error was parsed from
+    pub fn raw_errno(&self) -> c_int {
+        match self {
+            AdviseError::ENOMEM => libc::ENOMEM,
+            AdviseError::EFAULT => libc::EFAULT,
+            AdviseError::EINVAL => libc::EINVAL,
+            AdviseError::ENOSYS => libc::ENOSYS,
+            AdviseError::EUNIM(err) => *err,
+        }
+    }
+
+    /// Returns the `Errno` this error was parsed from
+    pub fn errno(&self) -> Errno {
+        Errno::from(self.raw_errno())
+    }
+
+    /// Returns `true` if the call may succeed when retried as is:
+    /// `EAGAIN` (a kernel resource is temporarily unavailable) and `EINTR`,
+    /// both only reported as `EUNIM`.
+    pub fn is_transient(&self) -> bool {
+        matches!(self.raw_errno(), libc::EAGAIN | libc::EINTR)
+    }
+
+    /// Builds an `AdviseError` from the current `errno`,
+    /// since `madvise` only returns `-1` on failure.
+    #[cfg(target_os = "linux")]
+    fn last_os_error() -> Self {
+        AdviseError::from(Errno::last().raw())
+    }
+}
+
 impl From<c_int> for AdviseError {
     fn from(err: c_int) -> Self {
         match err {
-            12 => AdviseError::ENOMEM,
-            14 => AdviseError::EFAULT,
-            22 => AdviseError::EINVAL,
-            38 => AdviseError::ENOSYS,
+            libc::ENOMEM => AdviseError::ENOMEM,
+            libc::EFAULT => AdviseError::EFAULT,
+            libc::EINVAL => AdviseError::EINVAL,
+            libc::ENOSYS => AdviseError::ENOSYS,
             _ => AdviseError::EUNIM(err),
         }
     }
-}
\ No newline at end of file
+}
+
+impl Display for AdviseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.errno(), f)
+    }
+}
+
+impl Error for AdviseError {}
@@ -0,0 +1,90 @@
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+
+use crate::wrappers::advisor::{Advise, AdviseError, Adviser};
+use crate::wrappers::locker::{LockError, Locker};
+
+/// A wrapper-struct `Guard` that protects the buffer's page for its whole lifetime:
+/// the page is locked and advised `WillNeed` on construction,
+/// then advised `DontNeed` and unlocked on drop, in that order.
+/// # Examples
+///
+/// ```
+/// use memguar::guard::Guard;
+///
+/// let buf = vec![420; 16_000];
+/// let mut guarded_buf = Guard::new(buf).unwrap();
+///
+/// guarded_buf.get_mut()[0] = 69;
+/// ```
+pub struct Guard<C: AsMut<[T]>, T> {
+    locker: Locker<C, T>,
+}
+
+impl<C: AsMut<[T]>, T> Guard<C, T> {
+    pub fn new(buf: C) -> Result<Self, GuardError> {
+        let mut locker = Locker::try_new(buf).map_err(|(_buf, err)| err)?;
+
+        Adviser::new(locker.get_mut()).syscall_advise(Advise::WillNeed)?;
+
+        Ok(Self { locker })
+    }
+
+    /// Returns the guarded buffer as a slice.
+    pub fn get(&self) -> &[T]
+    where
+        C: AsRef<[T]>,
+    {
+        self.locker.get()
+    }
+
+    /// Returns the guarded buffer as a mutable slice.
+    pub fn get_mut(&mut self) -> &mut [T] {
+        self.locker.get_mut()
+    }
+}
+
+impl<C: AsMut<[T]>, T> Drop for Guard<C, T> {
+    fn drop(&mut self) {
+        // Advise errors can't be reported from `drop`,
+        // the page is unlocked right after, when `locker` drops
+        let _ = Adviser::new(self.locker.get_mut()).syscall_advise(Advise::DontNeed);
+    }
+}
+
+/// Errors of `Guard`, from either locking or advising the buffer's page
+#[derive(Debug)]
+pub enum GuardError {
+    Lock(LockError),
+    Advise(AdviseError),
+}
+
+impl From<LockError> for GuardError {
+    fn from(err: LockError) -> Self {
+        GuardError::Lock(err)
+    }
+}
+
+impl From<AdviseError> for GuardError {
+    fn from(err: AdviseError) -> Self {
+        GuardError::Advise(err)
+    }
+}
+
+impl Display for GuardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GuardError::Lock(err) => write!(f, "cannot lock buffer: {err}"),
+            GuardError::Advise(err) => write!(f, "cannot advise buffer: {err}"),
+        }
+    }
+}
+
+impl Error for GuardError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GuardError::Lock(err) => Some(err),
+            GuardError::Advise(err) => Some(err),
+        }
+    }
+}
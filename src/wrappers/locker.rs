@@ -1,6 +1,19 @@
-use std::marker::PhantomData;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::BitOr;
+use core::ptr;
 
-use libc::{c_int, c_void, mlock, munlock};
+use libc::{
+    c_int, c_uint, c_void, getrlimit, MCL_CURRENT, MCL_FUTURE, mincore, mlock, mlockall, munlock,
+    munlockall, RLIM_INFINITY, rlimit, RLIMIT_MEMLOCK,
+};
+
+use crate::errno::Errno;
+use crate::page::page_size;
 
 /// A wrapper-Struct `Locker` that is used to lock the buffer's page.
 /// Locking memory pages ensures that those pages are not moved to the page file,
@@ -16,67 +29,684 @@ use libc::{c_int, c_void, mlock, munlock};
 ///     .lock()
 ///     .unwrap()
 /// ```
-#[repr(transparent)]
-pub struct Locker<C: AsMut<[T]>, T> {
-    pub buf: C,
+pub struct Locker<C, T> {
+    buf: C,
+    slice: fn(&mut C) -> &[T],
+    /// Address and size in bytes of the buffer when it was locked
+    locked: Option<(usize, usize)>,
+    /// Whether `lock_range` or `unlock_range` was used since the last `lock` or `unlock`
+    ranged: bool,
+    #[cfg(feature = "std")]
+    counted: bool,
     item_type: PhantomData<T>,
 }
 
-impl<C: AsMut<[T]>, T> Locker<C, T> {
-    pub fn new(buf: C) -> Self {
+impl<C, T> Locker<C, T> {
+    pub fn new(buf: C) -> Self
+    where
+        C: AsMut<[T]>,
+    {
+        Self::with_slice(buf, |buf| C::as_mut(buf))
+    }
+
+    /// Creates a `Locker` over a buffer that is only readable, such as an `Arc<[T]>`,
+    /// since locking only needs the address and length of the buffer.
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use memguar::locker::Locker;
+    ///
+    /// let buf: Arc<[i32]> = Arc::from([420; 16_000]);
+    /// let mut locked_buf = Locker::new_readonly(buf);
+    ///
+    /// locked_buf
+    ///     .lock()
+    ///     .unwrap()
+    /// ```
+    pub fn new_readonly(buf: C) -> Self
+    where
+        C: AsRef<[T]>,
+    {
+        Self::with_slice(buf, |buf| C::as_ref(buf))
+    }
+
+    fn with_slice(buf: C, slice: fn(&mut C) -> &[T]) -> Self {
         Self {
             buf,
+            slice,
+            locked: None,
+            ranged: false,
+            #[cfg(feature = "std")]
+            counted: false,
             item_type: PhantomData,
         }
     }
 
+    /// Makes `lock` and `unlock` count the locks of the buffer's pages process-wide,
+    /// since `mlock` isn't recursive and a single `munlock` undoes any number of locks.
+    /// Counted `Locker`s whose buffers share a page then only unlock it
+    /// once none of them has it locked anymore.
+    ///
+    /// Only `lock` and `unlock` of counted `Locker`s are counted, so ranges, `lock_with` flags,
+    /// uncounted `Locker`s and other `munlock` calls can still unlock the shared pages.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::Locker;
+    ///
+    /// let mut buf = vec![420; 16_000];
+    /// let (left, right) = buf.split_at_mut(8_000);
+    /// let mut locked_left = Locker::new(left).counted();
+    /// let mut locked_right = Locker::new(right).counted();
+    ///
+    /// locked_left.lock().unwrap();
+    /// locked_right.lock().unwrap();
+    /// // The page shared by both halves stays locked
+    /// locked_left.unlock().unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn counted(mut self) -> Self {
+        self.counted = true;
+        self
+    }
+
+    /// Creates a `Locker` with the buffer's page already locked.
+    /// On failure the buffer is handed back along with the error.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::Locker;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let _locked_buf = Locker::try_new(buf)
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// ```
+    pub fn try_new(buf: C) -> Result<Self, (C, LockError)>
+    where
+        C: AsMut<[T]>,
+    {
+        let mut locker = Self::new(buf);
+
+        match locker.lock() {
+            Ok(()) => Ok(locker),
+            Err(err) => Err((locker.into_inner(), err)),
+        }
+    }
+
     /// If `lock` is successful, the buffer's page locked,
     /// preventing it from being swapped out to disk/swap-zone.
+    ///
+    /// If locking fails because the buffer is larger than `RLIMIT_MEMLOCK` allows
+    /// (see `lockable_bytes`), `LimitExceeded` is returned instead of `ENOMEM`.
+    /// Locking an already locked buffer is a no-op,
+    /// unless `lock_range` or `unlock_range` was used since, then all of its pages are locked.
     pub fn lock(&mut self) -> Result<(), LockError> {
-        let buf = self.buf.as_mut();
+        let buf = (self.slice)(&mut self.buf);
         assert!(size_of_val(buf) > 0, "Zero size buffer");
-        let ptr = buf.as_mut_ptr() as *mut c_void;
-        let len = size_of_val(buf);
-        // SAFETY: FFI. Valid ptr (*mut c_void) and len + processed `mclock` result
-        let result = unsafe {
-            mlock(ptr, len)
+
+        if self.locked.is_some() && !self.ranged {
+            return Ok(());
+        }
+
+        // A counted buffer that is already locked keeps its single entry
+        #[cfg(feature = "std")]
+        let result = match self.counted && self.locked.is_none() {
+            true => lock_counted(buf.as_ptr() as *mut c_void, size_of_val(buf)),
+            false => lock(buf),
         };
+        #[cfg(not(feature = "std"))]
+        let result = lock(buf);
 
-        match result {
-            0 => Ok(()),
-            result => Err(LockError::from(result)),
+        if result.is_ok() {
+            self.locked = Some((buf.as_ptr() as usize, size_of_val(buf)));
+            self.ranged = false;
         }
+
+        result
+    }
+
+    /// If `relock` is successful, the lock follows the buffer after its pages have changed,
+    /// e.g. when the `Vec` behind `C` was reallocated elsewhere:
+    /// the pages locked before are unlocked and the buffer's current pages are locked.
+    /// Does nothing if the buffer hasn't moved, and only locks it if it wasn't locked.
+    ///
+    /// If the old pages can't be unlocked the error is returned and they stay tracked,
+    /// so `relock` (or `unlock`) can be retried. `ENOMEM` isn't reported there, since it means
+    /// the old pages are no longer mapped, which releases their locks as well.
+    pub fn relock(&mut self) -> Result<(), LockError> {
+        let buf = (self.slice)(&mut self.buf);
+        let current = (buf.as_ptr() as usize, size_of_val(buf));
+
+        if self.locked == Some(current) && !self.ranged {
+            return Ok(());
+        }
+
+        match self.unlock() {
+            Ok(()) | Err(LockError::ENOMEM) => self.locked = None,
+            Err(err) => return Err(err),
+        }
+
+        self.lock()
+    }
+
+    /// If `lock_on_fault` is successful, the buffer's pages are locked
+    /// as they are faulted in (`mlock2` with `MLOCK_ONFAULT`),
+    /// so pages that are never touched don't take up RAM.
+    #[cfg(target_os = "linux")]
+    pub fn lock_on_fault(&mut self) -> Result<(), LockError> {
+        self.lock_with(LockFlags::ONFAULT)
+    }
+
+    /// If `lock_with` is successful, the buffer's page is locked according to the flags
+    /// (`mlock2`). Empty flags fall back to `mlock`, while non-empty flags
+    /// return `ENOSYS` on platforms without `mlock2`.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::{LockFlags, Locker};
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let mut locked_buf = Locker::new(buf);
+    ///
+    /// locked_buf
+    ///     .lock_with(LockFlags::empty())
+    ///     .unwrap()
+    /// ```
+    pub fn lock_with(&mut self, flags: LockFlags) -> Result<(), LockError> {
+        if flags.is_empty() {
+            return self.lock();
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let buf = (self.slice)(&mut self.buf);
+            assert!(size_of_val(buf) > 0, "Zero size buffer");
+            let ptr = buf.as_ptr() as *mut c_void;
+            let len = size_of_val(buf);
+            // SAFETY: FFI. Valid ptr (*mut c_void), len and flags + processed `mlock2` result
+            let result = unsafe {
+                libc::mlock2(ptr, len, flags.0 as _)
+            };
+            let result = match result {
+                0 => Ok(()),
+                _ => Err(LockError::last_lock_error(len)),
+            };
+
+            trace_syscall!("mlock2", &result, ptr = ?ptr, len, flags = flags.0);
+
+            if result.is_ok() {
+                self.locked = Some((ptr as usize, len));
+                self.ranged = false;
+            }
+
+            result
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        Err(LockError::ENOSYS)
     }
 
     /// If `unlock` is successful, the buffer's page is unlocked,
     /// allowing the system to perform additional optimizations,
     /// such as moving pages to the swap file or merging adjacent locked memory regions.
+    /// Unlocking a buffer that isn't locked is a no-op.
     pub fn unlock(&mut self) -> Result<(), LockError> {
-        let buf = self.buf.as_mut();
-        assert!(size_of_val(buf) > 0, "zero size buffer");
-        let ptr = buf.as_mut_ptr() as *mut c_void;
-        let len = size_of_val(buf);
-        // SAFETY: FFI. Valid ptr (*mut c_void) and len + processed `munlock` result
+        // The pages locked last, even if the buffer has moved since
+        let Some((addr, len)) = self.locked else {
+            return Ok(());
+        };
+
+        #[cfg(feature = "std")]
+        let result = match self.counted {
+            true => unlock_counted(addr as *mut c_void, len),
+            false => unlock_pages(addr as *mut c_void, len),
+        };
+        #[cfg(not(feature = "std"))]
+        let result = unlock_pages(addr as *mut c_void, len);
+
+        if result.is_ok() {
+            self.locked = None;
+            self.ranged = false;
+        }
+
+        result
+    }
+
+    /// If `lock_range` is successful, the pages holding `len` elements
+    /// starting at the element `start` are locked.
+    /// The buffer then counts as locked, so `unlock` (and drop) unlock all of its pages,
+    /// while `lock` still locks the rest of them.
+    /// Returns `EINVAL` if the range is out of the buffer's bounds.
+    pub fn lock_range(&mut self, start: usize, len: usize) -> Result<(), LockError> {
+        let (ptr, len) = self.range(start, len)?;
+        // SAFETY: FFI. Valid ptr (*mut c_void) and len within the buffer + processed `mlock` result
+        let result = unsafe {
+            mlock(ptr, len)
+        };
+        let result = match result {
+            0 => Ok(()),
+            _ => Err(LockError::last_lock_error(len)),
+        };
+
+        trace_syscall!("mlock", &result, ptr = ?ptr, len);
+
+        if result.is_ok() {
+            if self.locked.is_none() {
+                let buf = (self.slice)(&mut self.buf);
+                self.locked = Some((buf.as_ptr() as usize, size_of_val(buf)));
+            }
+
+            self.ranged = true;
+        }
+
+        result
+    }
+
+    /// If `unlock_range` is successful, the pages holding `len` elements
+    /// starting at the element `start` are unlocked, so a later `lock` locks them again.
+    /// Returns `EINVAL` if the range is out of the buffer's bounds.
+    pub fn unlock_range(&mut self, start: usize, len: usize) -> Result<(), LockError> {
+        let (ptr, len) = self.range(start, len)?;
+        // SAFETY: FFI. Valid ptr (*mut c_void) and len within the buffer + processed `munlock` result
         let result = unsafe {
             munlock(ptr, len)
         };
+        let result = match result {
+            0 => Ok(()),
+            _ => Err(LockError::last_os_error()),
+        };
+
+        trace_syscall!("munlock", &result, ptr = ?ptr, len);
+
+        if result.is_ok() {
+            self.ranged = true;
+        }
+
+        result
+    }
+
+    fn range(&mut self, start: usize, len: usize) -> Result<(*mut c_void, usize), LockError> {
+        let buf = (self.slice)(&mut self.buf);
+
+        match start.checked_add(len) {
+            Some(end) if end <= buf.len() => {
+                let buf = &buf[start..end];
+                Ok((buf.as_ptr() as *mut c_void, size_of_val(buf)))
+            }
+            _ => Err(LockError::EINVAL),
+        }
+    }
+
+    /// If `residency` is successful, it returns one `bool` per page of the buffer,
+    /// indicating whether the page is resident in RAM (via `mincore`).
+    /// The page size is taken from `page_size`, and the first page
+    /// is the one containing the start of the buffer.
+    pub fn residency(&mut self) -> Result<Vec<bool>, LockError> {
+        let buf = (self.slice)(&mut self.buf);
+        assert!(size_of_val(buf) > 0, "Zero size buffer");
+        let page_size = page_size();
+        let addr = buf.as_ptr() as usize;
+        let start = addr - addr % page_size;
+        let len = addr + size_of_val(buf) - start;
+        let mut pages = vec![0u8; len.div_ceil(page_size)];
+        // SAFETY: FFI. Page aligned ptr (*mut c_void), len within the buffer's pages
+        // and `pages` holds one byte per page + processed `mincore` result
+        let result = unsafe {
+            mincore(start as *mut c_void, len, pages.as_mut_ptr().cast())
+        };
 
         match result {
-            0 => Ok(()),
-            result => Err(LockError::from(result)),
+            0 => Ok(pages.into_iter().map(|page| page & 1 == 1).collect()),
+            _ => Err(LockError::last_os_error()),
+        }
+    }
+
+    /// Clones the buffer into a new `Locker` and locks the copy's page.
+    /// The clone is locked independently, unlocking either one doesn't affect the other,
+    /// and it is `counted` if the original is, in case both share the same pages.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::Locker;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let locked_buf = Locker::try_new(buf)
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// let _locked_copy = locked_buf
+    ///     .try_clone()
+    ///     .unwrap();
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, LockError>
+    where
+        C: Clone,
+    {
+        let mut locker = Self::with_slice(self.buf.clone(), self.slice);
+        #[cfg(feature = "std")]
+        {
+            locker.counted = self.counted;
+        }
+        locker.lock()?;
+
+        Ok(locker)
+    }
+
+    /// Returns the locked buffer as a slice.
+    pub fn get(&self) -> &[T]
+    where
+        C: AsRef<[T]>,
+    {
+        self.buf.as_ref()
+    }
+
+    /// Returns the locked buffer as a mutable slice.
+    pub fn get_mut(&mut self) -> &mut [T]
+    where
+        C: AsMut<[T]>,
+    {
+        self.buf.as_mut()
+    }
+
+    /// Unlocks the buffer's page and returns the buffer.
+    /// Unlock errors are ignored here, use `close` to handle them.
+    pub fn into_inner(self) -> C {
+        match self.close() {
+            Ok(buf) | Err((buf, _)) => buf,
+        }
+    }
+
+    /// Unlocks the buffer's page and returns the buffer, or the buffer along with
+    /// the unlock error. Since the `Locker` is consumed, it doesn't unlock again on drop.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::Locker;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let locked_buf = Locker::try_new(buf)
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// let _buf = locked_buf
+    ///     .close()
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// ```
+    pub fn close(self) -> Result<C, (C, LockError)> {
+        let mut this = ManuallyDrop::new(self);
+        let result = this.unlock();
+        // SAFETY: `this` is never dropped, so `buf` is moved out exactly once
+        let buf = unsafe {
+            ptr::read(&this.buf)
+        };
+
+        match result {
+            Ok(()) => Ok(buf),
+            Err(err) => Err((buf, err)),
         }
     }
 }
 
-impl<C: AsMut<[T]>, T> Drop for Locker<C, T> {
+impl<C, T> Drop for Locker<C, T> {
     fn drop(&mut self) {
-        self.unlock()
-            .expect("Cant unlock while dropping")
+        // Unlock errors can't be reported from `drop`,
+        // use `unlock` before dropping to handle them
+        let _ = self.unlock();
+    }
+}
+
+/// Page ranges locked by counted `Locker`s, one entry per `lock`
+#[cfg(feature = "std")]
+static LOCKED_PAGES: std::sync::Mutex<Vec<(usize, usize)>> = std::sync::Mutex::new(Vec::new());
+
+/// Page aligned `(start, end)` of the pages holding `len` bytes at `ptr`
+#[cfg(feature = "std")]
+fn page_range(ptr: *mut c_void, len: usize) -> (usize, usize) {
+    let page_size = page_size();
+    let addr = ptr as usize;
+
+    (addr - addr % page_size, (addr + len).next_multiple_of(page_size))
+}
+
+/// Locks the pages and records them, while holding `LOCKED_PAGES`
+/// so no other counted `Locker` unlocks them in between.
+#[cfg(feature = "std")]
+fn lock_counted(ptr: *mut c_void, len: usize) -> Result<(), LockError> {
+    let mut locked_pages = LOCKED_PAGES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    // SAFETY: FFI. Valid ptr (*mut c_void) and len + processed `mlock` result
+    let result = unsafe {
+        mlock(ptr, len)
+    };
+    let result = match result {
+        0 => Ok(()),
+        _ => Err(LockError::last_lock_error(len)),
+    };
+
+    trace_syscall!("mlock", &result, ptr = ?ptr, len);
+
+    if result.is_ok() {
+        locked_pages.push(page_range(ptr, len));
+    }
+
+    result
+}
+
+/// Forgets one lock of the pages and unlocks those of them
+/// that aren't locked by any other counted `Locker`.
+#[cfg(feature = "std")]
+fn unlock_counted(ptr: *mut c_void, len: usize) -> Result<(), LockError> {
+    let mut locked_pages = LOCKED_PAGES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let (start, end) = page_range(ptr, len);
+
+    if let Some(index) = locked_pages.iter().position(|&pages| pages == (start, end)) {
+        locked_pages.swap_remove(index);
+    }
+
+    let mut still_locked: Vec<(usize, usize)> = locked_pages
+        .iter()
+        .filter(|&&(locked_start, locked_end)| locked_start < end && locked_end > start)
+        .copied()
+        .collect();
+    still_locked.sort_unstable();
+
+    let mut unlocked = start;
+
+    for (locked_start, locked_end) in still_locked.into_iter().chain([(end, end)]) {
+        if locked_start > unlocked {
+            let len = locked_start.min(end) - unlocked;
+            // SAFETY: FFI. Page aligned ptr (*mut c_void) and len within the buffer's pages
+            // + processed `munlock` result
+            let result = unsafe {
+                munlock(unlocked as *mut c_void, len)
+            };
+            let result = match result {
+                0 => Ok(()),
+                _ => Err(LockError::last_os_error()),
+            };
+
+            trace_syscall!("munlock", &result, ptr = unlocked, len);
+            result?;
+        }
+
+        unlocked = unlocked.max(locked_end);
+    }
+
+    Ok(())
+}
+
+/// If `lock` is successful, the pages of a borrowed buffer are locked (`mlock`),
+/// same as `Locker::lock`, but without wrapping the buffer,
+/// so nothing unlocks it automatically and `unlock` has to be called manually.
+/// # Examples
+///
+/// ```
+/// use memguar::locker::{lock, unlock};
+///
+/// let mut buf = vec![420; 16_000];
+///
+/// lock(&buf).unwrap();
+/// buf[0] = 69;
+/// unlock(&buf).unwrap();
+/// ```
+pub fn lock<T>(buf: &[T]) -> Result<(), LockError> {
+    let len = size_of_val(buf);
+    // SAFETY: FFI. Valid ptr (*mut c_void) and len, `mlock` doesn't write to the buffer
+    // + processed `mlock` result
+    let result = unsafe {
+        mlock(buf.as_ptr() as *mut c_void, len)
+    };
+    let result = match result {
+        0 => Ok(()),
+        _ => Err(LockError::last_lock_error(len)),
+    };
+
+    trace_syscall!("mlock", &result, ptr = ?buf.as_ptr(), len);
+    result
+}
+
+/// If `unlock` is successful, the pages of a borrowed buffer are unlocked (`munlock`).
+pub fn unlock<T>(buf: &[T]) -> Result<(), LockError> {
+    unlock_pages(buf.as_ptr() as *mut c_void, size_of_val(buf))
+}
+
+/// Unlocks the pages holding `len` bytes at `ptr`, which may no longer belong to the buffer
+fn unlock_pages(ptr: *mut c_void, len: usize) -> Result<(), LockError> {
+    // SAFETY: FFI. `munlock` only checks that the range is mapped, it doesn't access it
+    // + processed `munlock` result
+    let result = unsafe {
+        munlock(ptr, len)
+    };
+    let result = match result {
+        0 => Ok(()),
+        _ => Err(LockError::last_os_error()),
+    };
+
+    trace_syscall!("munlock", &result, ptr = ?ptr, len);
+    result
+}
+
+/// If `lock_all` is successful, all pages mapped by the process are locked,
+/// according to the specified flags.
+/// # Examples
+///
+/// ```no_run
+/// use memguar::locker::{lock_all, unlock_all, LockAllFlags};
+///
+/// lock_all(LockAllFlags::CURRENT | LockAllFlags::FUTURE).unwrap();
+/// unlock_all().unwrap();
+/// ```
+pub fn lock_all(flags: LockAllFlags) -> Result<(), LockError> {
+    // SAFETY: FFI. Valid flags + processed `mlockall` result
+    let result = unsafe {
+        mlockall(flags.0)
+    };
+    let result = match result {
+        0 => Ok(()),
+        _ => Err(LockError::last_os_error()),
+    };
+
+    trace_syscall!("mlockall", &result, flags = flags.0);
+    result
+}
+
+/// If `unlock_all` is successful, all pages mapped by the process are unlocked.
+pub fn unlock_all() -> Result<(), LockError> {
+    // SAFETY: FFI. Processed `munlockall` result
+    let result = unsafe {
+        munlockall()
+    };
+    let result = match result {
+        0 => Ok(()),
+        _ => Err(LockError::last_os_error()),
+    };
+
+    trace_syscall!("munlockall", &result, flags = 0);
+    result
+}
+
+/// If `lockable_bytes` is successful, it returns the soft `RLIMIT_MEMLOCK` limit,
+/// the number of bytes an unprivileged process may lock.
+/// `RLIM_INFINITY` is returned as `u64::MAX`.
+/// # Examples
+///
+/// ```
+/// use memguar::locker::lockable_bytes;
+///
+/// let limit = lockable_bytes().unwrap();
+/// ```
+pub fn lockable_bytes() -> Result<u64, LockError> {
+    let mut limit = rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: FFI. Valid resource and pointer to `limit` + processed `getrlimit` result
+    let result = unsafe {
+        getrlimit(RLIMIT_MEMLOCK, &mut limit)
+    };
+
+    match result {
+        0 if limit.rlim_cur == RLIM_INFINITY => Ok(u64::MAX),
+        0 => Ok(limit.rlim_cur as _),
+        _ => Err(LockError::last_os_error()),
+    }
+}
+
+/// Combinable flags for `lock_all`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockAllFlags(c_int);
+
+impl LockAllFlags {
+    /// Lock all pages that are currently mapped (`MCL_CURRENT`)
+    pub const CURRENT: Self = Self(MCL_CURRENT);
+    /// Lock all pages that will become mapped in the future (`MCL_FUTURE`)
+    pub const FUTURE: Self = Self(MCL_FUTURE);
+}
+
+impl BitOr for LockAllFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Combinable flags for `Locker::lock_with`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockFlags(c_uint);
+
+impl LockFlags {
+    /// Lock pages as they are faulted in (`MLOCK_ONFAULT`), Linux only
+    #[cfg(target_os = "linux")]
+    pub const ONFAULT: Self = Self(libc::MLOCK_ONFAULT as c_uint);
+
+    /// No flags, plain `mlock`
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns `true` if no flags are set
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for LockFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
     }
 }
 
 /// Parsed types of `mlock` and `munlock` errors
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum LockError {
     EPERM,
     EINTR,
@@ -87,22 +717,102 @@ pub enum LockError {
     EBUSY,
     EINVAL,
     ENOSYS,
+    /// `ENOMEM` caused by locking more bytes than `RLIMIT_MEMLOCK` allows
+    LimitExceeded {
+        requested: u64,
+        limit: u64,
+    },
     EUNIM(c_int),
 }
 
+impl LockError {
+    /// Fallible counterpart of `From<c_int>` that returns `None` for `0`,
+    /// since it means success rather than an error.
+    /// (`TryFrom<c_int>` can't be implemented alongside `From<c_int>`)
+    pub fn try_from_errno(err: c_int) -> Option<Self> {
+        match err {
+            0 => None,
+            err => Some(LockError::from(err)),
+        }
+    }
+
+    /// Returns the raw `errno` value this error was parsed from
+    pub fn raw_errno(&self) -> c_int {
+        match self {
+            LockError::EPERM => libc::EPERM,
+            LockError::EINTR => libc::EINTR,
+            LockError::EIO => libc::EIO,
+            LockError::EAGAIN => libc::EAGAIN,
+            LockError::ENOMEM | LockError::LimitExceeded { .. } => libc::ENOMEM,
+            LockError::EFAULT => libc::EFAULT,
+            LockError::EBUSY => libc::EBUSY,
+            LockError::EINVAL => libc::EINVAL,
+            LockError::ENOSYS => libc::ENOSYS,
+            LockError::EUNIM(err) => *err,
+        }
+    }
+
+    /// Returns the `Errno` this error was parsed from
+    pub fn errno(&self) -> Errno {
+        Errno::from(self.raw_errno())
+    }
+
+    /// Returns `true` if the call may succeed when retried as is:
+    /// `EAGAIN` (some pages could not be locked for now) and `EINTR`.
+    /// `LimitExceeded` and the other errors persist until the request or its limits change.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, LockError::EAGAIN | LockError::EINTR)
+    }
+
+    /// Builds a `LockError` from the current `errno`,
+    /// since `mlock`/`munlock` only return `-1` on failure.
+    pub(crate) fn last_os_error() -> Self {
+        LockError::from(Errno::last().raw())
+    }
+
+    /// Same as `last_os_error`, but `ENOMEM` is reported as `LimitExceeded`
+    /// if `requested` bytes are more than `RLIMIT_MEMLOCK` allows.
+    /// Privileged processes don't fail with `ENOMEM` there, so they are never reported.
+    pub(crate) fn last_lock_error(requested: usize) -> Self {
+        match (LockError::last_os_error(), lockable_bytes()) {
+            (LockError::ENOMEM, Ok(limit)) if requested as u64 > limit => {
+                LockError::LimitExceeded {
+                    requested: requested as u64,
+                    limit,
+                }
+            }
+            (err, _) => err,
+        }
+    }
+}
+
 impl From<c_int> for LockError {
     fn from(err: c_int) -> Self {
         match err {
-            1 => LockError::EPERM,
-            4 => LockError::EINTR,
-            5 => LockError::EIO,
-            11 => LockError::EAGAIN,
-            12 => LockError::ENOMEM,
-            14 => LockError::EFAULT,
-            16 => LockError::EBUSY,
-            22 => LockError::EINVAL,
-            38 => LockError::ENOSYS,
+            libc::EPERM => LockError::EPERM,
+            libc::EINTR => LockError::EINTR,
+            libc::EIO => LockError::EIO,
+            libc::EAGAIN => LockError::EAGAIN,
+            libc::ENOMEM => LockError::ENOMEM,
+            libc::EFAULT => LockError::EFAULT,
+            libc::EBUSY => LockError::EBUSY,
+            libc::EINVAL => LockError::EINVAL,
+            libc::ENOSYS => LockError::ENOSYS,
             _ => LockError::EUNIM(err),
         }
     }
-}
\ No newline at end of file
+}
+
+impl Display for LockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::LimitExceeded { requested, limit } => write!(
+                f,
+                "locked memory limit exceeded ({requested} bytes requested, limit is {limit} bytes)"
+            ),
+            _ => Display::fmt(&self.errno(), f),
+        }
+    }
+}
+
+impl Error for LockError {}
@@ -0,0 +1,327 @@
+use core::error::Error;
+use core::ffi::{c_int, c_void};
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use windows_sys::Win32::Foundation::{
+    ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER, ERROR_NOACCESS, ERROR_NOT_ENOUGH_MEMORY,
+    ERROR_NOT_LOCKED, ERROR_WORKING_SET_QUOTA, GetLastError, WIN32_ERROR,
+};
+use windows_sys::Win32::System::Memory::{VirtualLock, VirtualUnlock};
+
+/// A wrapper-Struct `Locker` that is used to lock the buffer's page.
+/// Locking memory pages ensures that those pages are not moved to the page file,
+/// # Examples
+///
+/// ```
+/// use memguar::locker::Locker;
+///
+/// let buf = [420; 16_000];
+/// let mut locked_buf = Locker::new(buf);
+///
+/// locked_buf
+///     .lock()
+///     .unwrap()
+/// ```
+pub struct Locker<C, T> {
+    buf: C,
+    slice: fn(&mut C) -> &[T],
+    /// Address and size in bytes of the buffer when it was locked
+    locked: Option<(usize, usize)>,
+    item_type: PhantomData<T>,
+}
+
+impl<C, T> Locker<C, T> {
+    pub fn new(buf: C) -> Self
+    where
+        C: AsMut<[T]>,
+    {
+        Self::with_slice(buf, |buf| C::as_mut(buf))
+    }
+
+    /// Creates a `Locker` over a buffer that is only readable, such as an `Arc<[T]>`,
+    /// since locking only needs the address and length of the buffer.
+    pub fn new_readonly(buf: C) -> Self
+    where
+        C: AsRef<[T]>,
+    {
+        Self::with_slice(buf, |buf| C::as_ref(buf))
+    }
+
+    fn with_slice(buf: C, slice: fn(&mut C) -> &[T]) -> Self {
+        Self {
+            buf,
+            slice,
+            locked: None,
+            item_type: PhantomData,
+        }
+    }
+
+    /// Creates a `Locker` with the buffer's page already locked.
+    /// On failure the buffer is handed back along with the error.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::Locker;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let _locked_buf = Locker::try_new(buf)
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// ```
+    pub fn try_new(buf: C) -> Result<Self, (C, LockError)>
+    where
+        C: AsMut<[T]>,
+    {
+        let mut locker = Self::new(buf);
+
+        match locker.lock() {
+            Ok(()) => Ok(locker),
+            Err(err) => Err((locker.into_inner(), err)),
+        }
+    }
+
+    /// If `lock` is successful, the buffer's page locked,
+    /// preventing it from being swapped out to the page file.
+    /// Locking an already locked buffer is a no-op.
+    pub fn lock(&mut self) -> Result<(), LockError> {
+        let buf = (self.slice)(&mut self.buf);
+        assert!(size_of_val(buf) > 0, "Zero size buffer");
+
+        if self.locked.is_some() {
+            return Ok(());
+        }
+
+        let result = lock(buf);
+
+        if result.is_ok() {
+            self.locked = Some((buf.as_ptr() as usize, size_of_val(buf)));
+        }
+
+        result
+    }
+
+    /// If `relock` is successful, the lock follows the buffer after its pages have changed,
+    /// e.g. when the `Vec` behind `C` was reallocated elsewhere:
+    /// the pages locked before are unlocked and the buffer's current pages are locked.
+    /// Does nothing if the buffer hasn't moved, and only locks it if it wasn't locked.
+    /// If the old pages can't be unlocked the error is returned and they stay tracked.
+    pub fn relock(&mut self) -> Result<(), LockError> {
+        let buf = (self.slice)(&mut self.buf);
+        let current = (buf.as_ptr() as usize, size_of_val(buf));
+
+        if self.locked == Some(current) {
+            return Ok(());
+        }
+
+        self.unlock()?;
+        self.lock()
+    }
+
+    /// If `unlock` is successful, the buffer's page is unlocked,
+    /// allowing the system to move it to the page file.
+    /// Unlocking a buffer that isn't locked is a no-op.
+    pub fn unlock(&mut self) -> Result<(), LockError> {
+        // The pages locked last, even if the buffer has moved since
+        let Some((addr, len)) = self.locked else {
+            return Ok(());
+        };
+
+        let result = unlock_pages(addr as *const c_void, len);
+
+        if result.is_ok() {
+            self.locked = None;
+        }
+
+        result
+    }
+
+    /// Clones the buffer into a new `Locker` and locks the copy's page.
+    /// The clone is locked independently, unlocking either one doesn't affect the other.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::Locker;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let locked_buf = Locker::try_new(buf)
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// let _locked_copy = locked_buf
+    ///     .try_clone()
+    ///     .unwrap();
+    /// ```
+    pub fn try_clone(&self) -> Result<Self, LockError>
+    where
+        C: Clone,
+    {
+        let mut locker = Self::with_slice(self.buf.clone(), self.slice);
+        locker.lock()?;
+
+        Ok(locker)
+    }
+
+    /// Returns the locked buffer as a slice.
+    pub fn get(&self) -> &[T]
+    where
+        C: AsRef<[T]>,
+    {
+        self.buf.as_ref()
+    }
+
+    /// Returns the locked buffer as a mutable slice.
+    pub fn get_mut(&mut self) -> &mut [T]
+    where
+        C: AsMut<[T]>,
+    {
+        self.buf.as_mut()
+    }
+
+    /// Unlocks the buffer's page and returns the buffer.
+    /// Unlock errors are ignored here, use `close` to handle them.
+    pub fn into_inner(self) -> C {
+        match self.close() {
+            Ok(buf) | Err((buf, _)) => buf,
+        }
+    }
+
+    /// Unlocks the buffer's page and returns the buffer, or the buffer along with
+    /// the unlock error. Since the `Locker` is consumed, it doesn't unlock again on drop.
+    /// # Examples
+    ///
+    /// ```
+    /// use memguar::locker::Locker;
+    ///
+    /// let buf = vec![420; 16_000];
+    /// let locked_buf = Locker::try_new(buf)
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// let _buf = locked_buf
+    ///     .close()
+    ///     .map_err(|(_buf, err)| err)
+    ///     .unwrap();
+    /// ```
+    pub fn close(self) -> Result<C, (C, LockError)> {
+        let mut this = ManuallyDrop::new(self);
+        let result = this.unlock();
+        // SAFETY: `this` is never dropped, so `buf` is moved out exactly once
+        let buf = unsafe {
+            ptr::read(&this.buf)
+        };
+
+        match result {
+            Ok(()) => Ok(buf),
+            Err(err) => Err((buf, err)),
+        }
+    }
+}
+
+impl<C, T> Drop for Locker<C, T> {
+    fn drop(&mut self) {
+        // Unlock errors can't be reported from `drop`,
+        // use `unlock` before dropping to handle them
+        let _ = self.unlock();
+    }
+}
+
+/// If `lock` is successful, the pages of a borrowed buffer are locked (`VirtualLock`),
+/// same as `Locker::lock`, but without wrapping the buffer,
+/// so nothing unlocks it automatically and `unlock` has to be called manually.
+pub fn lock<T>(buf: &[T]) -> Result<(), LockError> {
+    // SAFETY: FFI. Valid ptr (*const c_void) and len + processed `VirtualLock` result
+    let result = unsafe {
+        VirtualLock(buf.as_ptr() as *const c_void, size_of_val(buf))
+    };
+
+    match result {
+        0 => Err(LockError::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// If `unlock` is successful, the pages of a borrowed buffer are unlocked (`VirtualUnlock`).
+pub fn unlock<T>(buf: &[T]) -> Result<(), LockError> {
+    unlock_pages(buf.as_ptr() as *const c_void, size_of_val(buf))
+}
+
+/// Unlocks the pages holding `len` bytes at `ptr`, which may no longer belong to the buffer
+fn unlock_pages(ptr: *const c_void, len: usize) -> Result<(), LockError> {
+    // SAFETY: FFI. `VirtualUnlock` only checks that the range is locked, it doesn't access it
+    // + processed `VirtualUnlock` result
+    let result = unsafe {
+        VirtualUnlock(ptr, len)
+    };
+
+    match result {
+        0 => Err(LockError::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Parsed types of `VirtualLock` and `VirtualUnlock` errors,
+/// named after their closest Unix counterparts
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LockError {
+    EPERM,
+    EINTR,
+    EIO,
+    EAGAIN,
+    ENOMEM,
+    EFAULT,
+    EBUSY,
+    EINVAL,
+    ENOSYS,
+    EUNIM(c_int),
+}
+
+impl LockError {
+    /// Builds a `LockError` from `GetLastError`,
+    /// since `VirtualLock`/`VirtualUnlock` only return `0` on failure.
+    pub(crate) fn last_os_error() -> Self {
+        // SAFETY: FFI. No preconditions
+        let err = unsafe { GetLastError() };
+
+        LockError::from(err)
+    }
+
+    /// Returns `true` if the call may succeed when retried as is:
+    /// `EAGAIN` (the working set quota is exhausted for now) and `EINTR`.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, LockError::EAGAIN | LockError::EINTR)
+    }
+}
+
+impl From<WIN32_ERROR> for LockError {
+    fn from(err: WIN32_ERROR) -> Self {
+        match err {
+            ERROR_ACCESS_DENIED => LockError::EPERM,
+            ERROR_WORKING_SET_QUOTA => LockError::EAGAIN,
+            ERROR_NOT_ENOUGH_MEMORY => LockError::ENOMEM,
+            ERROR_NOACCESS => LockError::EFAULT,
+            ERROR_INVALID_PARAMETER | ERROR_NOT_LOCKED => LockError::EINVAL,
+            _ => LockError::EUNIM(err as c_int),
+        }
+    }
+}
+
+impl Display for LockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::EPERM => write!(f, "operation not permitted"),
+            LockError::EINTR => write!(f, "interrupted system call"),
+            LockError::EIO => write!(f, "input/output error"),
+            LockError::EAGAIN => write!(f, "resource temporarily unavailable"),
+            LockError::ENOMEM => write!(f, "cannot allocate memory"),
+            LockError::EFAULT => write!(f, "bad address"),
+            LockError::EBUSY => write!(f, "device or resource busy"),
+            LockError::EINVAL => write!(f, "invalid argument"),
+            LockError::ENOSYS => write!(f, "function not implemented"),
+            LockError::EUNIM(err) => write!(f, "unknown error (code {err})"),
+        }
+    }
+}
+
+impl Error for LockError {}